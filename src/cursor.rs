@@ -0,0 +1,325 @@
+//! Independent read positions into a buffer.
+
+use core::cmp;
+use core::fmt;
+
+use super::WheelBuf;
+
+/// A reader that remembers its position in a WheelBuf.
+///
+/// The cursor does not borrow the buffer, so the owner can keep pushing
+/// between reads. Each read yields only items the cursor has not seen yet;
+/// any number of cursors can read the same buffer independently. If items
+/// are overwritten before the cursor gets to them, `next()` silently skips
+/// ahead to the oldest item still stored, while `recv()` reports the gap.
+///
+/// Positions are tracked by sequence number (see `WheelBuf::first_seq()`),
+/// so changes that renumber the buffer, such as removing items from the
+/// middle or retracting them with `pop_back()`, make the cursor read the
+/// remaining items again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ReaderCursor {
+    /// Sequence number of the next unread item
+    next: usize,
+
+    /// Items overwritten before they could be read
+    missed: usize,
+}
+
+/// A fixed view of the items stored at the time it was taken.
+///
+/// Like a cursor, a snapshot does not borrow the buffer. It yields only the
+/// items that were stored when it was created, in logical order, no matter
+/// how many items are pushed in the meantime. Captured items that get
+/// overwritten before being read are skipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Snapshot {
+    /// Sequence number of the next captured item
+    next: usize,
+
+    /// Sequence number one past the newest captured item
+    end: usize,
+}
+
+/// Error returned by `ReaderCursor::recv()` if the cursor fell behind.
+///
+/// Holds the number of items that were overwritten before they could be
+/// read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Lagged(pub usize);
+
+impl fmt::Display for Lagged {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "reader lagged behind by {} items", self.0)
+    }
+}
+
+/// Iterator over the unread items of a ReaderCursor.
+///
+/// Advances the cursor as items are yielded.
+#[derive(Debug)]
+pub struct ReaderIter<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+    cursor: &'a mut ReaderCursor,
+    buffer: &'a WheelBuf<C, I>,
+}
+
+impl ReaderCursor {
+    /// Returns the next unread item and advances the cursor.
+    #[inline]
+    pub fn next<'a, C, I>(&mut self, buffer: &'a WheelBuf<C, I>) -> Option<&'a I>
+        where C: AsMut<[I]> + AsRef<[I]>
+    {
+        self.catch_up(buffer);
+
+        let start = buffer.seq_start();
+        let item = buffer.get(self.next - start)?;
+        self.next += 1;
+        Some(item)
+    }
+
+    /// Returns the next unread item and advances the cursor, reporting
+    /// overwritten items.
+    ///
+    /// If the next item the cursor would have read was overwritten, returns
+    /// `Lagged` with the number of missed items and moves the cursor to the
+    /// oldest stored item, which the following call returns.
+    #[inline]
+    pub fn recv<'a, C, I>(&mut self, buffer: &'a WheelBuf<C, I>) -> Result<Option<&'a I>, Lagged>
+        where C: AsMut<[I]> + AsRef<[I]>
+    {
+        match self.catch_up(buffer) {
+            0 => Ok(self.next(buffer)),
+            missed => Err(Lagged(missed)),
+        }
+    }
+
+    /// Total number of items overwritten before the cursor could read them.
+    #[inline]
+    pub fn missed(&self) -> usize {
+        self.missed
+    }
+
+    /// Moves the cursor up to the oldest item of `buffer` if it fell
+    /// behind, returning the number of skipped items that were overwritten.
+    ///
+    /// Items the owner removed, e.g. with `pop_front()`, are skipped without
+    /// being counted.
+    #[inline]
+    fn catch_up<C, I>(&mut self, buffer: &WheelBuf<C, I>) -> usize
+        where C: AsMut<[I]> + AsRef<[I]>
+    {
+        let start = buffer.seq_start();
+        if self.next >= start {
+            return 0;
+        }
+
+        let overwritten = cmp::min(buffer.overwritten, start).saturating_sub(self.next);
+        self.next = start;
+        self.missed += overwritten;
+        overwritten
+    }
+
+    /// Number of items stored in `buffer` that the cursor has not read.
+    #[inline]
+    pub fn pending<C, I>(&self, buffer: &WheelBuf<C, I>) -> usize
+        where C: AsMut<[I]> + AsRef<[I]>
+    {
+        buffer.seq_end().saturating_sub(cmp::max(self.next, buffer.seq_start()))
+    }
+
+    /// Creates an iterator over the unread items of `buffer`.
+    #[inline]
+    pub fn read<'a, C, I>(&'a mut self, buffer: &'a WheelBuf<C, I>) -> ReaderIter<'a, C, I>
+        where C: AsMut<[I]> + AsRef<[I]>
+    {
+        ReaderIter {
+            cursor: self,
+            buffer,
+        }
+    }
+}
+
+impl Snapshot {
+    /// Returns the next captured item that is still stored.
+    #[inline]
+    pub fn next<'a, C, I>(&mut self, buffer: &'a WheelBuf<C, I>) -> Option<&'a I>
+        where C: AsMut<[I]> + AsRef<[I]>
+    {
+        self.next = cmp::max(self.next, buffer.seq_start());
+        if self.next >= self.end {
+            return None;
+        }
+
+        let item = buffer.get_seq(self.next)?;
+        self.next += 1;
+        Some(item)
+    }
+
+    /// Number of captured items not yet read, including overwritten ones.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.end.saturating_sub(self.next)
+    }
+
+    /// Returns `true` if none of the unread captured items have been
+    /// overwritten in `buffer`.
+    #[inline]
+    pub fn is_intact<C, I>(&self, buffer: &WheelBuf<C, I>) -> bool
+        where C: AsMut<[I]> + AsRef<[I]>
+    {
+        self.next >= cmp::min(buffer.seq_start(), self.end)
+    }
+}
+
+impl<'a, C, I> Iterator for ReaderIter<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+    type Item = &'a I;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursor.next(self.buffer)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.cursor.pending(self.buffer);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<C, I> WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    /// Creates a cursor positioned at the oldest item.
+    #[inline]
+    pub fn cursor(&self) -> ReaderCursor {
+        ReaderCursor {
+            next: self.seq_start(),
+            missed: 0,
+        }
+    }
+
+    /// Creates a cursor that only sees items pushed from now on.
+    #[inline]
+    pub fn cursor_at_end(&self) -> ReaderCursor {
+        ReaderCursor {
+            next: self.seq_end(),
+            missed: 0,
+        }
+    }
+
+    /// Captures the items currently stored.
+    #[inline]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            next: self.seq_start(),
+            end: self.seq_end(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+    use std::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn independent_readers() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        wheel.extend(1..3);
+
+        let mut a = wheel.cursor();
+        let mut b = wheel.cursor_at_end();
+        assert_eq!(a.pending(&wheel), 2);
+        assert_eq!(b.pending(&wheel), 0);
+
+        assert_eq!(a.next(&wheel), Some(&1));
+        wheel.push(3);
+
+        let v: Vec<i32> = a.read(&wheel).cloned().collect();
+        assert_eq!(v, [2, 3]);
+        let v: Vec<i32> = b.read(&wheel).cloned().collect();
+        assert_eq!(v, [3]);
+        assert!(a.next(&wheel).is_none());
+    }
+
+    #[test]
+    fn overwritten() {
+        let mut wheel = WheelBuf::new([0; 3]);
+        let mut cursor = wheel.cursor();
+
+        wheel.extend(1..7);
+        assert_eq!(cursor.pending(&wheel), 3);
+
+        let v: Vec<i32> = cursor.read(&wheel).cloned().collect();
+        assert_eq!(v, [4, 5, 6]);
+        assert_eq!(cursor.missed(), 3);
+
+        let mut text = WheelBuf::new(['x'; 2]);
+        let mut cursor = text.cursor();
+        write!(text, "abcde").unwrap();
+        assert_eq!(cursor.recv(&text), Err(Lagged(3)));
+        assert_eq!(cursor.next(&text), Some(&'d'));
+    }
+
+    #[test]
+    fn lagged() {
+        let mut wheel = WheelBuf::new([0; 3]);
+        let mut cursor = wheel.cursor();
+
+        wheel.extend(1..3);
+        assert_eq!(cursor.recv(&wheel), Ok(Some(&1)));
+
+        wheel.extend(3..7);
+        assert_eq!(cursor.recv(&wheel), Err(Lagged(2)));
+        assert_eq!(cursor.recv(&wheel), Ok(Some(&4)));
+        assert_eq!(cursor.recv(&wheel), Ok(Some(&5)));
+        assert_eq!(cursor.recv(&wheel), Ok(Some(&6)));
+        assert_eq!(cursor.recv(&wheel), Ok(None));
+        assert_eq!(cursor.missed(), 2);
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        wheel.extend(1..4);
+
+        let mut snapshot = wheel.snapshot();
+        assert_eq!(snapshot.remaining(), 3);
+        assert_eq!(snapshot.next(&wheel), Some(&1));
+
+        wheel.extend(4..6);
+        assert!(snapshot.is_intact(&wheel));
+        wheel.push(6);
+        assert!(!snapshot.is_intact(&wheel));
+        assert_eq!(snapshot.next(&wheel), Some(&3));
+        assert!(snapshot.next(&wheel).is_none());
+        assert_eq!(snapshot.remaining(), 0);
+    }
+
+    #[test]
+    fn missed_pop_front() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        let mut cursor = wheel.cursor();
+
+        wheel.extend(1..4);
+        wheel.pop_front();
+        assert_eq!(cursor.recv(&wheel), Ok(Some(&2)));
+        assert_eq!(cursor.missed(), 0);
+
+        // Only the overwritten item counts, not the popped one before it.
+        wheel.extend(4..8);
+        wheel.pop_front();
+        assert_eq!(cursor.recv(&wheel), Err(Lagged(1)));
+        assert_eq!(cursor.next(&wheel), Some(&5));
+        assert_eq!(cursor.missed(), 1);
+    }
+}
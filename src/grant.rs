@@ -0,0 +1,117 @@
+//! Zero-copy access to the free and the filled parts of a buffer.
+
+use core::cmp;
+
+use super::WheelBuf;
+
+impl<C, I> WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    /// Returns up to `n` free slots following the newest item, to be filled
+    /// in place.
+    ///
+    /// The slots are returned as two slices, the second of which is only
+    /// non-empty if the free space wraps around the end of the backing
+    /// store. They hold stale items; nothing is added to the buffer until
+    /// `commit()` is called. Fewer than `n` slots are returned if the buffer
+    /// does not have that much free space.
+    #[inline]
+    pub fn grant(&mut self, n: usize) -> (&mut [I], &mut [I]) {
+        let n = cmp::min(n, self.capacity() - self.len);
+        let pos = self.pos;
+        let (wrapped, tail) = self.data.as_mut().split_at_mut(pos);
+        let first_len = cmp::min(n, tail.len());
+
+        (&mut tail[..first_len], &mut wrapped[..n - first_len])
+    }
+
+    /// Adds the first `n` slots handed out by `grant()` to the buffer.
+    ///
+    /// Counts as `n` pushes towards `total()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` exceeds the free space of the buffer.
+    #[inline]
+    pub fn commit(&mut self, n: usize) {
+        assert!(n <= self.capacity() - self.len,
+                "commit of {} items exceeds free space of {}",
+                n,
+                self.capacity() - self.len);
+
+        if n == 0 {
+            return;
+        }
+
+        self.pos = self.wrap(self.pos + n);
+        self.len += n;
+        self.total += n;
+        self.seq += n;
+    }
+
+    /// Returns the stored items, oldest first, to be read in place.
+    ///
+    /// Same as `as_slices()`; the items stay in the buffer until
+    /// `release()` is called.
+    #[inline]
+    pub fn read_grant(&self) -> (&[I], &[I]) {
+        self.as_slices()
+    }
+
+    /// Removes the `n` oldest items after reading them through
+    /// `read_grant()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` exceeds the number of stored items.
+    #[inline]
+    pub fn release(&mut self, n: usize) {
+        assert!(n <= self.len, "release of {} items exceeds len of {}", n, self.len);
+        self.len -= n;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn grant_commit() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        wheel.extend(1..4);
+        wheel.pop_front();
+
+        {
+            let (first, second) = wheel.grant(8);
+            assert_eq!((first.len(), second.len()), (1, 1));
+            first[0] = 4;
+            second[0] = 5;
+        }
+        wheel.commit(2);
+
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [2, 3, 4, 5]);
+        assert_eq!(wheel.total(), 5);
+        assert_eq!(wheel.grant(1), (&mut [][..], &mut [][..]));
+    }
+
+    #[test]
+    fn read_grant_release() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        wheel.extend(1..6);
+
+        assert_eq!(wheel.read_grant(), (&[2, 3, 4][..], &[5][..]));
+        wheel.release(3);
+        assert_eq!(wheel.read_grant(), (&[5][..], &[][..]));
+        assert_eq!(wheel.total(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn commit_overflow() {
+        let mut wheel = WheelBuf::new([0; 2]);
+        wheel.push(1);
+        wheel.commit(2);
+    }
+}
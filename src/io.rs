@@ -0,0 +1,68 @@
+//! Standard IO traits for byte buffers, available with the `std` feature.
+
+use core::cmp;
+use std::io;
+
+use super::{Overflow, WheelBuf};
+
+impl<C> io::Read for WheelBuf<C, u8>
+    where C: AsMut<[u8]> + AsRef<[u8]>
+{
+    /// Moves the oldest bytes into `buf`.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.peek_into(buf);
+        let len = self.len();
+        self.truncate_front(len - n);
+        Ok(n)
+    }
+}
+
+impl<C> io::Write for WheelBuf<C, u8>
+    where C: AsMut<[u8]> + AsRef<[u8]>
+{
+    /// Pushes the bytes of `buf`.
+    ///
+    /// Under `Overflow::Reject` only as many bytes as there is room for are
+    /// written, so `write_all()` fails with `WriteZero` on a full buffer.
+    /// The other policies accept all bytes.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let free = self.capacity() - self.len();
+        self.write_bytes(buf);
+
+        match self.overflow {
+            Overflow::Reject => Ok(cmp::min(buf.len(), free)),
+            _ => Ok(buf.len()),
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read, Write};
+    use super::*;
+
+    #[test]
+    fn read_write() {
+        let mut wheel = WheelBuf::new([0u8; 4]);
+        wheel.write_all(b"abcdef").unwrap();
+
+        let mut out = [0; 3];
+        assert_eq!(wheel.read(&mut out).unwrap(), 3);
+        assert_eq!(&out, b"cde");
+        assert_eq!(wheel.len(), 1);
+
+        wheel.set_overflow(Overflow::Reject);
+        assert_eq!(wheel.write(b"xyz!").unwrap(), 3);
+        assert_eq!(wheel.write_all(b"!").unwrap_err().kind(), io::ErrorKind::WriteZero);
+
+        let mut s = std::vec::Vec::new();
+        io::copy(&mut wheel, &mut s).unwrap();
+        assert_eq!(s, b"fxyz");
+        assert!(wheel.is_empty());
+    }
+}
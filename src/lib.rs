@@ -6,19 +6,63 @@
 //! offers the slice interface, e.g. a vector or even a static array.
 //!
 //! The create performs no allocations itself and does not use the standard
-//! library (`#![no_std]`).
+//! library (`#![no_std]`). With the `alloc` feature enabled, buffers can also
+//! allocate their own backing store on the heap. The `async` feature lets the
+//! halves of an `spsc` queue wake tasks waiting on each other, and
+//! `cache-padded` keeps the indices of the concurrent queues on separate
+//! cache lines. The `std` feature implements `std::io::Read` and `Write`
+//! for byte buffers.
 
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 use core::cmp;
 use core::convert::AsRef;
 use core::marker::PhantomData;
+use core::mem;
+use core::ops::{Bound, Index, IndexMut, RangeBounds};
+use core::slice;
+
+mod cursor;
+mod grant;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(target_has_atomic = "ptr")]
+pub mod mpmc;
+#[cfg(target_has_atomic = "ptr")]
+pub mod ordering;
+#[cfg(feature = "alloc")]
+mod owned;
+#[cfg(target_has_atomic = "ptr")]
+mod padded;
+#[cfg(target_has_atomic = "ptr")]
+pub mod spsc;
+mod split;
+pub mod stats;
+mod storage;
+mod timed;
+pub mod timer;
+mod uninit;
+#[cfg(all(feature = "async", target_has_atomic = "ptr"))]
+mod waker;
+mod windows;
+
+pub use cursor::{Lagged, ReaderCursor, ReaderIter, Snapshot};
+pub use split::{WheelBufConsumer, WheelBufProducer};
+pub use storage::Storage;
+pub use timed::TtlWheelBuf;
+pub use uninit::UninitWheelBuf;
+pub use windows::{WheelBufChunks, WheelBufLines, WheelBufWindows};
+use core::fmt;
 use core::fmt::Write;
+use core::hash::{Hash, Hasher};
+use core::iter::{FusedIterator, StepBy};
 
-//! A multi-read Ringbuffer.
-//!
-//! The Write trait is implemented for `char` buffers, see below.
-#[derive(Debug)]
+/// A multi-read Ringbuffer.
+///
+/// The Write trait is implemented for `char` buffers, see below.
 pub struct WheelBuf<C, I>
     where C: AsMut<[I]> + AsRef<[I]>
 {
@@ -28,13 +72,98 @@ pub struct WheelBuf<C, I>
     /// Insert position
     pos: usize,
 
+    /// Number of items currently stored
+    len: usize,
+
     /// Total items written
     total: usize,
 
+    /// Sequence number of the next push, never decreases
+    seq: usize,
+
+    /// Sequence number one past the newest overwritten item
+    overwritten: usize,
+
+    /// Whether the newest overwrite may have cut a line in two, see
+    /// `lines()`
+    line_cut: bool,
+
+    /// `capacity - 1` for power-of-two capacities, see `mask_for()`
+    mask: usize,
+
+    /// Behavior of pushes into a full buffer
+    overflow: Overflow,
+
     _pd: PhantomData<I>,
 }
 
-//! WheelBuf iterator
+/// A WheelBuf backed by an inline array, capacity `N` is part of the type.
+pub type ArrayWheelBuf<I, const N: usize> = WheelBuf<[I; N], I>;
+
+/// What a push into a full buffer does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// Overwrite the oldest item (the default).
+    Overwrite,
+
+    /// Discard the new item, keeping the buffer contents unchanged.
+    Saturate,
+
+    /// Discard the new item and report an error where possible.
+    ///
+    /// `write_str()` returns `fmt::Error` once a character is rejected.
+    Reject,
+}
+
+/// Error returned by `try_push()` if the buffer is full.
+///
+/// Hands back the rejected item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PushError<I>(pub I);
+
+impl<I> PushError<I> {
+    /// Returns the item that could not be pushed.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.0
+    }
+}
+
+impl<I> fmt::Display for PushError<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("wheel buffer is full")
+    }
+}
+
+/// The state of a WheelBuf, as returned by `into_raw_parts()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RawParts<C> {
+    /// Backend store
+    pub data: C,
+
+    /// Insert position, the physical index of the next push
+    pub pos: usize,
+
+    /// Number of items currently stored, ending just before `pos`
+    pub len: usize,
+
+    /// Total items written
+    pub total: usize,
+}
+
+/// Error returned by `from_raw_parts()` if the parts are inconsistent.
+///
+/// Hands back the rejected parts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawPartsError<C>(pub RawParts<C>);
+
+impl<C> fmt::Display for RawPartsError<C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("inconsistent wheel buffer state")
+    }
+}
+
+/// WheelBuf iterator
 #[derive(Debug)]
 pub struct WheelBufIter<'a, C, I>
     where C: AsMut<[I]> + AsRef<[I]>,
@@ -43,6 +172,54 @@ pub struct WheelBufIter<'a, C, I>
 {
     buffer: &'a WheelBuf<C, I>,
     cur: usize,
+    end: usize,
+}
+
+/// WheelBuf iterator yielding sequence numbers along with items
+#[derive(Debug)]
+pub struct WheelBufIndexed<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+    iter: WheelBufIter<'a, C, I>,
+    seq_start: usize,
+}
+
+/// Consuming WheelBuf iterator
+///
+/// Yields the items by value, oldest first.
+#[derive(Debug)]
+pub struct WheelBufIntoIter<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    buffer: WheelBuf<C, I>,
+}
+
+/// Draining WheelBuf iterator
+///
+/// Removes a range of items, yielding them by value. The gap left behind is
+/// closed when the iterator is dropped.
+#[derive(Debug)]
+pub struct WheelBufDrain<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a + Default,
+          C: 'a
+{
+    buffer: &'a mut WheelBuf<C, I>,
+    start: usize,
+    drain_end: usize,
+    cur: usize,
+    end: usize,
+}
+
+/// Mutable WheelBuf iterator
+#[derive(Debug)]
+pub struct WheelBufIterMut<'a, I>
+    where I: 'a
+{
+    first: slice::IterMut<'a, I>,
+    second: slice::IterMut<'a, I>,
 }
 
 impl<C, I> WheelBuf<C, I>
@@ -52,16 +229,126 @@ impl<C, I> WheelBuf<C, I>
     ///
     /// `data` is a backing data structure that must be convertible into a
     /// slice. The `len()` of data determines the size of the buffer.
+    ///
+    /// This is a `const fn`, so a buffer can be placed in a `static`. As
+    /// such, it cannot look at `data` to tell whether the capacity is a
+    /// power of two, and leaves that to every wraparound. The other
+    /// constructors check once, which saves the time on targets without a
+    /// hardware divider. Array stores have a constant capacity, so the
+    /// check folds away either way.
     #[inline]
-    pub fn new(data: C) -> WheelBuf<C, I> {
+    pub const fn new(data: C) -> WheelBuf<C, I> {
         WheelBuf {
-            data: data,
+            data,
             pos: 0,
+            len: 0,
             total: 0,
+            seq: 0,
+            overwritten: 0,
+            line_cut: false,
+            mask: UNKNOWN_MASK,
+            overflow: Overflow::Overwrite,
             _pd: PhantomData,
         }
     }
 
+    /// Creates a new WheelBuf, checking the capacity for a power of two.
+    #[inline]
+    fn from_store(data: C) -> WheelBuf<C, I> {
+        let mask = mask_for(data.as_ref().len());
+        let mut wheel = WheelBuf::new(data);
+        wheel.mask = mask;
+        wheel
+    }
+
+    /// Creates a new WheelBuf that treats all of `data` as already pushed.
+    ///
+    /// The buffer starts out full, with `data[0]` as the oldest item.
+    #[inline]
+    pub fn new_full(data: C) -> WheelBuf<C, I> {
+        let len = data.as_ref().len();
+        WheelBuf::new_with_len(data, len)
+    }
+
+    /// Creates a new WheelBuf that treats the first `len` items of `data` as
+    /// already pushed.
+    ///
+    /// `data[0]` is the oldest item. The pushed items count towards
+    /// `total()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than the length of `data`.
+    pub fn new_with_len(data: C, len: usize) -> WheelBuf<C, I> {
+        let cap = data.as_ref().len();
+        assert!(len <= cap, "length {} exceeds capacity {}", len, cap);
+
+        let mut wheel = WheelBuf::from_store(data);
+        wheel.pos = if len == cap { 0 } else { len };
+        wheel.len = len;
+        wheel.total = len;
+        wheel.seq = len;
+        wheel
+    }
+
+    /// Reconstructs a WheelBuf from its state.
+    ///
+    /// Fails if `pos` is not a valid index into `data` (or zero for an empty
+    /// store), if `len` exceeds the capacity or if `total` is less than
+    /// `len`. The overflow policy starts out as `Overwrite`.
+    pub fn from_raw_parts(parts: RawParts<C>) -> Result<WheelBuf<C, I>, RawPartsError<C>> {
+        let cap = parts.data.as_ref().len();
+        let pos_valid = parts.pos < cap || parts.pos == 0;
+        if !pos_valid || parts.len > cap || parts.total < parts.len {
+            return Err(RawPartsError(parts));
+        }
+
+        let mut wheel = WheelBuf::from_store(parts.data);
+        wheel.pos = parts.pos;
+        wheel.len = parts.len;
+        wheel.total = parts.total;
+        wheel.seq = parts.total;
+        Ok(wheel)
+    }
+
+    /// Decomposes the buffer into its state, e.g. for persisting it.
+    ///
+    /// The result can be passed to `from_raw_parts()` to restore the
+    /// buffer.
+    #[inline]
+    pub fn into_raw_parts(self) -> RawParts<C> {
+        RawParts {
+            data: self.data,
+            pos: self.pos,
+            len: self.len,
+            total: self.total,
+        }
+    }
+
+    /// Consumes the buffer, returning the backing store.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.data
+    }
+
+    /// Returns a reference to the backing store.
+    ///
+    /// Items are laid out physically, not in logical order, and slots
+    /// outside the buffer contents may hold stale items.
+    #[inline]
+    pub fn data(&self) -> &C {
+        &self.data
+    }
+
+    /// Returns the backing store as a mutable slice.
+    ///
+    /// Only the slice is exposed, so that the capacity cannot change
+    /// underneath the buffer. See `data()` for the layout.
+    #[inline]
+    pub fn data_mut(&mut self) -> &mut [I] {
+        self.data.as_mut()
+    }
+
     /// Total number of entries seen.
     ///
     /// A non-resetting counter of the number of entries added.
@@ -70,166 +357,2716 @@ impl<C, I> WheelBuf<C, I>
         self.total
     }
 
-    /// Add item to wheel buffer.
+    /// Overflow policy of the buffer.
     #[inline]
-    pub fn push(&mut self, item: I) {
-        self.data.as_mut()[self.pos] = item;
-        self.total += 1;
-        self.pos = (self.pos + 1) % self.data.as_ref().len();
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
     }
 
-    /// Capacity of wheel buffer.
-    ///
-    /// Always equal to `len()` of underlying `data`.
+    /// Sets the overflow policy, which controls what pushing into a full
+    /// buffer does.
     #[inline]
-    pub fn capacity(&self) -> usize {
-        self.data.as_ref().len()
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
     }
 
-    /// Number of items in buffer.
+    /// Sequence number of the oldest item, if any.
+    ///
+    /// Every push is numbered, starting at zero, and numbers are never
+    /// handed out twice. The number of an item stays the same while newer
+    /// items are pushed or older ones removed, so it can be used to refer to
+    /// the item stably. Any other change to the order, such as `pop_back()`
+    /// or `insert()`, gives all remaining items fresh numbers, so the old
+    /// ones no longer refer to anything.
     #[inline]
-    pub fn len(&self) -> usize {
-        cmp::min(self.total, self.capacity())
+    pub fn first_seq(&self) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+
+        Some(self.seq_start())
     }
 
-    /// Creates an iterator over buffer.
+    /// Sequence number of the most recently pushed item, if any.
+    ///
+    /// See `first_seq()`.
     #[inline]
-    pub fn iter<'a>(&'a self) -> WheelBufIter<'a, C, I> {
-        WheelBufIter {
-            buffer: &self,
-            cur: 0,
+    pub fn last_seq(&self) -> Option<usize> {
+        if self.is_empty() {
+            return None;
         }
+
+        Some(self.seq - 1)
     }
 
+    /// Returns a reference to the item with sequence number `seq`.
+    ///
+    /// Returns `None` if the item has been overwritten or removed, or has
+    /// not been pushed yet.
     #[inline]
-    fn read_start(&self) -> usize {
-        self.pos - (self.len() % self.capacity())
+    pub fn get_seq(&self, seq: usize) -> Option<&I> {
+        self.get(seq.checked_sub(self.seq_start())?)
     }
-}
 
-impl<'a, C, I> Iterator for WheelBufIter<'a, C, I>
-    where C: AsMut<[I]> + AsRef<[I]>,
-          I: 'a,
-          C: 'a
-{
-    type Item = &'a I;
+    /// Returns a mutable reference to the item with sequence number `seq`.
+    ///
+    /// See `get_seq()`.
+    #[inline]
+    pub fn get_seq_mut(&mut self, seq: usize) -> Option<&mut I> {
+        let index = seq.checked_sub(self.seq_start())?;
+        self.get_mut(index)
+    }
 
+    /// Add item to wheel buffer.
+    ///
+    /// If the buffer is full, the overflow policy decides whether the oldest
+    /// item is overwritten or `item` is discarded. A buffer without capacity
+    /// discards every item.
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.cur >= self.buffer.len() {
-            return None;
+    pub fn push(&mut self, item: I) {
+        let full = self.is_full();
+        if full && (self.overflow != Overflow::Overwrite || self.len == 0) {
+            return;
         }
 
-        let cur = self.cur;
-        self.cur += 1;
-        Some(&self.buffer.data.as_ref()[(self.buffer.read_start() + cur) % self.buffer.capacity()])
+        self.data.as_mut()[self.pos] = item;
+        self.total += 1;
+        self.seq += 1;
+        self.len += !full as usize;
+        self.pos = self.next_pos();
+        if full {
+            self.mark_overwritten();
+        }
     }
 
+    /// Add item to wheel buffer, returning the item it displaced.
+    ///
+    /// If the buffer is full, returns either the overwritten oldest item or,
+    /// if the overflow policy forbids overwriting or the buffer has no
+    /// capacity, `item` itself. Otherwise returns `None`.
     #[inline]
-    fn nth(&mut self, n: usize) -> Option<Self::Item> {
-        let max_idx = cmp::min(self.buffer.total, self.buffer.capacity());
+    pub fn push_evict(&mut self, item: I) -> Option<I> {
+        let full = self.is_full();
+        match self.replace_next(item) {
+            Ok(old) if full => Some(old),
+            Ok(_) => None,
+            Err(item) => Some(item),
+        }
+    }
 
-        if n > 0 {
-            self.cur += cmp::min(n, max_idx);
+    /// Add all items of a slice to wheel buffer.
+    ///
+    /// Equivalent to pushing each item in turn, but copies whole runs into
+    /// the backing store instead of handling items one by one.
+    pub fn extend_from_slice(&mut self, items: &[I])
+        where I: Clone
+    {
+        let cap = self.capacity();
+        if cap == 0 {
+            return;
         }
 
-        self.next()
+        let mut items = items;
+        let overwrites = self.overflow == Overflow::Overwrite && self.len + items.len() > cap;
+        if self.overflow != Overflow::Overwrite {
+            items = &items[..cmp::min(items.len(), cap - self.len)];
+        } else if items.len() > cap {
+            // Everything but the last `cap` items would be overwritten right
+            // away, so skip over them.
+            let skip = items.len() - cap;
+            self.total += skip;
+            self.seq += skip;
+            self.pos = self.wrap(self.pos + skip);
+            items = &items[skip..];
+        }
+
+        while !items.is_empty() {
+            let n = cmp::min(items.len(), cap - self.pos);
+            let pos = self.pos;
+            self.data.as_mut()[pos..pos + n].clone_from_slice(&items[..n]);
+
+            self.total += n;
+            self.seq += n;
+            self.len = cmp::min(self.len + n, cap);
+            self.pos = self.wrap(self.pos + n);
+            items = &items[n..];
+        }
+
+        if overwrites {
+            self.mark_overwritten();
+        }
     }
-}
 
-impl<C> Write for WheelBuf<C, char>
-    where C: AsMut<[char]> + AsRef<[char]>
-{
-    fn write_str(&mut self, s: &str) -> Result<(), core::fmt::Error> {
-        for c in s.chars() {
-            self.push(c)
+    /// Fills the whole buffer with clones of `value`.
+    ///
+    /// Afterwards the buffer is full. Counts as `capacity()` pushes towards
+    /// `total()`, regardless of the overflow policy.
+    pub fn fill(&mut self, value: I)
+        where I: Clone
+    {
+        for slot in self.data.as_mut() {
+            slot.clone_from(&value);
+        }
+
+        let overwrites = !self.is_empty();
+        self.pos = 0;
+        self.len = self.capacity();
+        self.total += self.capacity();
+        self.seq += self.capacity();
+        if overwrites {
+            self.mark_overwritten();
         }
-        Ok(())
     }
-}
 
-#[cfg(test)]
-#[macro_use]
-extern crate std;
+    /// Add item to wheel buffer, unless it is full.
+    ///
+    /// Never overwrites; if the buffer is full the item is returned inside
+    /// the error.
+    #[inline]
+    pub fn try_push(&mut self, item: I) -> Result<(), PushError<I>> {
+        if self.is_full() {
+            return Err(PushError(item));
+        }
 
-#[cfg(test)]
-mod tests {
-    use core::fmt::Write;
-    use std::string::String;
-    use super::*;
+        self.push(item);
+        Ok(())
+    }
 
-    #[test]
-    fn basics() {
-        let mut buf = ['x'; 8];
-        let mut wheel = WheelBuf::new(&mut buf);
+    /// Removes the most recently pushed item from the buffer.
+    ///
+    /// The vacated slot is reset to `I::default()`. A retracted item no
+    /// longer counts towards `total()`, and the remaining items get fresh
+    /// sequence numbers. Returns `None` if the buffer is empty.
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<I>
+        where I: Default
+    {
+        if self.is_empty() {
+            return None;
+        }
 
-        wheel.push('H');
-        wheel.push('e');
-        wheel.push('l');
-        assert_eq!(wheel.len(), 3);
-        assert_eq!(*wheel.iter().next().unwrap(), 'H');
+        self.pos = self.wrap(self.pos + self.capacity() - 1);
+        self.len -= 1;
+        self.total -= 1;
+        self.renumber();
+        Some(mem::take(&mut self.data.as_mut()[self.pos]))
+    }
 
-        wheel.push('l');
-        wheel.push('o');
-        wheel.push(' ');
-        wheel.push('W');
-        wheel.push('o');
-        wheel.push('r');
-        wheel.push('l');
-        wheel.push('d');
-        assert_eq!(wheel.len(), 8);
+    /// Removes the oldest item from the buffer.
+    ///
+    /// The vacated slot is reset to `I::default()`. Returns `None` if the
+    /// buffer is empty.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<I>
+        where I: Default
+    {
+        if self.is_empty() {
+            return None;
+        }
 
-        let s: String = wheel.iter().cloned().collect();
-        assert_eq!(s.as_str(), "lo World");
+        let idx = self.read_start();
+        self.len -= 1;
+        Some(mem::take(&mut self.data.as_mut()[idx]))
     }
 
-    #[test]
-    fn nth() {
-        let mut buf = ['x'; 8];
-        let mut wheel = WheelBuf::new(&mut buf);
+    /// Inserts `item` at logical position `index`, shifting later items
+    /// towards the back.
+    ///
+    /// If the buffer is full and the overflow policy is `Overwrite`, the
+    /// oldest item is evicted to make room and returned; inserting at index
+    /// `0` of a full buffer evicts `item` itself. Under the other policies a
+    /// full buffer hands `item` back unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than `len()`.
+    pub fn insert(&mut self, index: usize, item: I) -> Option<I> {
+        assert!(index <= self.len, "insertion index {} is out of bounds for length {}", index, self.len);
 
-        wheel.push('H');
-        wheel.push('e');
-        wheel.push('l');
+        if self.is_full() {
+            if index == 0 || self.overflow != Overflow::Overwrite {
+                return Some(item);
+            }
 
-        assert_eq!(*wheel.iter().nth(0).unwrap(), 'H');
-        assert_eq!(*wheel.iter().nth(1).unwrap(), 'e');
-        assert_eq!(*wheel.iter().nth(2).unwrap(), 'l');
-        assert!(wheel.iter().nth(3).is_none());
+            let front = self.physical(0);
+            let old = mem::replace(&mut self.data.as_mut()[front], item);
+            for i in 0..index - 1 {
+                self.swap_items(i, i + 1);
+            }
+            self.total += 1;
+            self.seq += 1;
+            self.mark_overwritten();
+            if index < self.len {
+                self.renumber();
+            }
+            return Some(old);
+        }
+
+        if index < self.len / 2 {
+            let front = self.wrap(self.read_start() + self.capacity() - 1);
+            self.data.as_mut()[front] = item;
+            self.len += 1;
+            for i in 0..index {
+                self.swap_items(i, i + 1);
+            }
+            self.total += 1;
+            self.renumber();
+        } else {
+            self.push(item);
+            for i in (index..self.len - 1).rev() {
+                self.swap_items(i, i + 1);
+            }
+            if index < self.len - 1 {
+                self.renumber();
+            }
+        }
+
+        None
     }
 
-    #[test]
-    fn write() {
-        let mut buf = ['x'; 8];
-        let mut wheel = WheelBuf::new(&mut buf);
+    /// Removes and returns the item at logical position `index`.
+    ///
+    /// The gap is closed by shifting whichever side holds fewer items, the
+    /// vacated slot is reset to `I::default()`. Returns `None` if `index` is
+    /// out of bounds.
+    pub fn remove(&mut self, index: usize) -> Option<I>
+        where I: Default
+    {
+        if index >= self.len {
+            return None;
+        }
 
-        write!(wheel, "Hello, World! {}", 123).unwrap();
-        let s: String = wheel.iter().cloned().collect();
-        assert_eq!(s.as_str(), "rld! 123");
+        let idx = self.physical(index);
+        let item = mem::take(&mut self.data.as_mut()[idx]);
+        self.close_gap(index, 1);
+        Some(item)
     }
 
-    #[test]
-    fn using_vec() {
-        let mut buf = vec!['x', 'x', 'x', 'x', 'x', 'x', 'x', 'x'];
-        let mut wheel = WheelBuf::new(&mut buf);
+    /// Swaps the items at logical positions `a` and `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    #[inline]
+    pub fn swap(&mut self, a: usize, b: usize) {
+        assert!(a < self.len, "index {} is out of bounds for length {}", a, self.len);
+        assert!(b < self.len, "index {} is out of bounds for length {}", b, self.len);
 
-        wheel.push('H');
-        wheel.push('e');
-        wheel.push('l');
-        assert_eq!(wheel.len(), 3);
-        assert_eq!(*wheel.iter().next().unwrap(), 'H');
+        if a != b {
+            self.swap_items(a, b);
+            self.renumber();
+        }
+    }
 
-        wheel.push('l');
-        wheel.push('o');
-        wheel.push(' ');
-        wheel.push('W');
-        wheel.push('o');
-        wheel.push('r');
-        wheel.push('l');
-        wheel.push('d');
-        assert_eq!(wheel.len(), 8);
+    /// Swaps the items at logical positions `a` and `b`, keeping their
+    /// sequence numbers in place.
+    #[inline]
+    fn swap_items(&mut self, a: usize, b: usize) {
+        let a = self.physical(a);
+        let b = self.physical(b);
+        self.data.as_mut().swap(a, b);
+    }
+
+    /// Gives all stored items fresh sequence numbers.
+    #[inline]
+    fn renumber(&mut self) {
+        self.seq += self.len;
+    }
+
+    /// Rotates the contents `n` places to the left.
+    ///
+    /// The item at logical index `n` becomes the first item. A full buffer is
+    /// rotated in O(1) by moving its insert position; otherwise
+    /// `min(n, len() - n)` items are moved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than `len()`.
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "rotation {} is out of bounds for length {}", n, self.len);
+        if n == 0 {
+            // Also keeps zero-capacity buffers from dividing by zero.
+            return;
+        }
+
+        self.renumber();
+        if self.is_full() {
+            self.pos = self.wrap(self.pos + n);
+        } else if n <= self.len - n {
+            for _ in 0..n {
+                let front = self.physical(0);
+                self.data.as_mut().swap(front, self.pos);
+                self.pos = self.wrap(self.pos + 1);
+            }
+        } else {
+            self.rotate_right(self.len - n);
+        }
+    }
+
+    /// Rotates the contents `n` places to the right.
+    ///
+    /// The item at logical index `len() - n` becomes the first item. Has
+    /// the same cost as `rotate_left()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than `len()`.
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "rotation {} is out of bounds for length {}", n, self.len);
+        if n == 0 {
+            return;
+        }
+
+        self.renumber();
+        let cap = self.capacity();
+        if self.is_full() {
+            self.pos = self.wrap(self.pos + cap - n);
+        } else if n <= self.len - n {
+            for _ in 0..n {
+                let back = self.wrap(self.pos + cap - 1);
+                let free = self.wrap(self.read_start() + cap - 1);
+                self.data.as_mut().swap(back, free);
+                self.pos = back;
+            }
+        } else {
+            self.rotate_left(self.len - n);
+        }
+    }
+
+    /// Removes the item at logical position `index`, replacing it with the
+    /// newest item.
+    ///
+    /// Runs in O(1) but does not preserve ordering. The vacated slot is reset
+    /// to `I::default()`. Returns `None` if `index` is out of bounds.
+    pub fn swap_remove_back(&mut self, index: usize) -> Option<I>
+        where I: Default
+    {
+        if index >= self.len {
+            return None;
+        }
+
+        let last = self.len - 1;
+        self.swap_items(index, last);
+
+        let idx = self.physical(last);
+        let item = mem::take(&mut self.data.as_mut()[idx]);
+        self.pos = idx;
+        self.len -= 1;
+        self.renumber();
+        Some(item)
+    }
+
+    /// Removes all items from the buffer.
+    ///
+    /// Runs in O(1), stale items are left in the backing store until they are
+    /// overwritten. `total()` is not reset.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.pos = 0;
+        self.len = 0;
+    }
+
+    /// Removes all items from the buffer, dropping them.
+    ///
+    /// Like `clear()`, but every live slot is reset to `I::default()`, which
+    /// releases resources held by owned items.
+    pub fn clear_drop(&mut self)
+        where I: Default
+    {
+        for i in 0..self.len {
+            let idx = self.physical(i);
+            self.data.as_mut()[idx] = I::default();
+        }
+
+        self.clear();
+    }
+
+    /// Shortens the buffer to the newest `len` items, removing the oldest.
+    ///
+    /// Has no effect if the buffer holds `len` items or fewer.
+    #[inline]
+    pub fn truncate_front(&mut self, len: usize) {
+        self.len = cmp::min(self.len, len);
+    }
+
+    /// Shortens the buffer to the oldest `len` items, removing the newest.
+    ///
+    /// Has no effect if the buffer holds `len` items or fewer. As with
+    /// `pop_back()`, the removed items no longer count towards `total()`
+    /// and the remaining items get fresh sequence numbers.
+    #[inline]
+    pub fn truncate_back(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        let removed = self.len - len;
+        self.pos = self.wrap(self.pos + self.capacity() - removed);
+        self.len = len;
+        self.total -= removed;
+        self.renumber();
+    }
+
+    /// Retains only the items matching `predicate`.
+    ///
+    /// Remaining items keep their logical order and are compacted towards the
+    /// oldest end. Removed items are dropped, their slots are reset to
+    /// `I::default()`.
+    pub fn retain<P>(&mut self, mut predicate: P)
+        where P: FnMut(&I) -> bool,
+              I: Default
+    {
+        let mut kept = 0;
+
+        for i in 0..self.len {
+            let idx = self.physical(i);
+            if predicate(&self.data.as_ref()[idx]) {
+                if kept != i {
+                    self.swap_items(kept, i);
+                }
+                kept += 1;
+            }
+        }
+
+        for i in kept..self.len {
+            let idx = self.physical(i);
+            self.data.as_mut()[idx] = I::default();
+        }
+
+        let removed = self.len - kept;
+        if removed > 0 {
+            self.pos = self.wrap(self.pos + self.capacity() - removed);
+            self.len = kept;
+            self.renumber();
+        }
+    }
+
+    /// Removes the items in the logical `range`, returning them as an
+    /// iterator.
+    ///
+    /// Items not yet yielded when the iterator is dropped are removed as
+    /// well. Vacated slots are reset to `I::default()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end or if the
+    /// end is greater than `len()`.
+    pub fn drain<'a, R>(&'a mut self, range: R) -> WheelBufDrain<'a, C, I>
+        where R: RangeBounds<usize>,
+              I: Default
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1).expect("attempted to drain up to maximum usize"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.checked_add(1).expect("attempted to drain up to maximum usize"),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.len,
+        };
+        assert!(start <= end, "drain start {} is greater than end {}", start, end);
+        assert!(end <= self.len, "drain end {} is out of bounds for length {}", end, self.len);
+
+        WheelBufDrain {
+            buffer: self,
+            start,
+            drain_end: end,
+            cur: start,
+            end,
+        }
+    }
+
+    /// Removes `count` items starting at logical index `start`.
+    ///
+    /// Whichever side of the gap holds fewer items is shifted to close it;
+    /// the removed items end up in the freed slots.
+    fn close_gap(&mut self, start: usize, count: usize) {
+        if count == 0 {
+            return;
+        }
+
+        let after = self.len - start - count;
+        if start < after {
+            for i in (0..start).rev() {
+                self.swap_items(i, i + count);
+            }
+        } else {
+            for i in start + count..self.len {
+                self.swap_items(i - count, i);
+            }
+            self.pos = self.wrap(self.pos + self.capacity() - count);
+        }
+
+        self.len -= count;
+        if start > 0 {
+            self.renumber();
+        }
+    }
+
+    /// Capacity of wheel buffer.
+    ///
+    /// Always equal to `len()` of underlying `data`.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.data.as_ref().len()
+    }
+
+    /// Number of items in buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the buffer holds no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the buffer is filled to capacity.
+    ///
+    /// If the buffer is full, the next `push()` will overwrite the oldest
+    /// item.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Returns a reference to the oldest item, if any.
+    #[inline]
+    pub fn front(&self) -> Option<&I> {
+        self.get(0)
+    }
+
+    /// Returns a reference to the most recently pushed item, if any.
+    #[inline]
+    pub fn back(&self) -> Option<&I> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.get(self.len - 1)
+    }
+
+    /// Returns a reference to the item at logical position `index`.
+    ///
+    /// Index `0` is the oldest item. Returns `None` if `index` is out of
+    /// bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&I> {
+        if index >= self.len {
+            return None;
+        }
+
+        Some(&self.data.as_ref()[self.physical(index)])
+    }
+
+    /// Returns a mutable reference to the item at logical position `index`.
+    ///
+    /// Uses the same indexing as `get()`.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut I> {
+        if index >= self.len {
+            return None;
+        }
+
+        let idx = self.physical(index);
+        Some(&mut self.data.as_mut()[idx])
+    }
+
+    /// Returns a reference to the item at logical position `index`, without
+    /// bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be less than `len()`. Indexing past the logical length
+    /// is undefined behavior, even if it stays within `capacity()`.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> &I {
+        debug_assert!(index < self.len, "index {} out of bounds for length {}", index, self.len);
+        // The position is reduced modulo the length of the very slice it
+        // indexes, so it is in bounds whatever `C::as_ref()` returns.
+        let start = self.read_start();
+        let data = self.data.as_ref();
+        unsafe { data.get_unchecked(wrap(start + index, data.len())) }
+    }
+
+    /// Returns a mutable reference to the item at logical position `index`,
+    /// without bounds checking.
+    ///
+    /// # Safety
+    ///
+    /// Same contract as `get_unchecked()`: `index` must be less than
+    /// `len()`.
+    #[inline]
+    pub unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut I {
+        debug_assert!(index < self.len, "index {} out of bounds for length {}", index, self.len);
+        let start = self.read_start();
+        let data = self.data.as_mut();
+        let len = data.len();
+        unsafe { data.get_unchecked_mut(wrap(start + index, len)) }
+    }
+
+    /// Creates an iterator over buffer.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> WheelBufIter<'a, C, I> {
+        WheelBufIter {
+            buffer: self,
+            cur: 0,
+            end: self.len,
+        }
+    }
+
+    /// Creates an iterator over `(sequence number, item)` pairs, oldest
+    /// first.
+    ///
+    /// See `first_seq()` for how items are numbered. Subtract `first_seq()`
+    /// to get logical indices instead.
+    #[inline]
+    pub fn iter_indexed<'a>(&'a self) -> WheelBufIndexed<'a, C, I> {
+        WheelBufIndexed {
+            iter: self.iter(),
+            seq_start: self.seq_start(),
+        }
+    }
+
+    /// Creates an iterator over the newest `n` items, oldest first.
+    ///
+    /// Use `latest(n).rev()` to get them newest first. Yields all items if
+    /// the buffer holds fewer than `n`.
+    #[inline]
+    pub fn latest<'a>(&'a self, n: usize) -> WheelBufIter<'a, C, I> {
+        WheelBufIter {
+            buffer: self,
+            cur: self.len - cmp::min(n, self.len),
+            end: self.len,
+        }
+    }
+
+    /// Creates an iterator over the items from sequence number `seq` on.
+    ///
+    /// If `seq` has already been overwritten, iteration starts at the oldest
+    /// item. See `first_seq()` for how items are numbered.
+    #[inline]
+    pub fn iter_from<'a>(&'a self, seq: usize) -> WheelBufIter<'a, C, I> {
+        WheelBufIter {
+            buffer: self,
+            cur: cmp::min(seq.saturating_sub(self.seq_start()), self.len),
+            end: self.len,
+        }
+    }
+
+    /// Creates an iterator over every `step`-th item, starting with the
+    /// oldest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is zero.
+    #[inline]
+    pub fn iter_step<'a>(&'a self, step: usize) -> StepBy<WheelBufIter<'a, C, I>> {
+        self.iter().step_by(step)
+    }
+
+    /// Rearranges the backing store so that the contents form a single
+    /// contiguous slice, which is returned.
+    ///
+    /// Items are only moved if the contents currently wrap around the end of
+    /// the backing store. Logical order is preserved.
+    pub fn make_contiguous(&mut self) -> &mut [I] {
+        if self.is_empty() {
+            return &mut [];
+        }
+
+        let start = self.read_start();
+        let len = self.len;
+        let cap = self.capacity();
+
+        if start + len <= cap {
+            return &mut self.data.as_mut()[start..start + len];
+        }
+
+        self.data.as_mut().rotate_left(start);
+        self.pos = self.wrap(len);
+        &mut self.data.as_mut()[..len]
+    }
+
+    /// Creates an iterator over buffer that allows modifying each item.
+    #[inline]
+    pub fn iter_mut<'a>(&'a mut self) -> WheelBufIterMut<'a, I> {
+        let (first, second) = self.as_mut_slices();
+        WheelBufIterMut {
+            first: first.iter_mut(),
+            second: second.iter_mut(),
+        }
+    }
+
+    /// Returns `true` if the buffer holds an item equal to `item`.
+    #[inline]
+    pub fn contains(&self, item: &I) -> bool
+        where I: PartialEq
+    {
+        let (first, second) = self.as_slices();
+        first.contains(item) || second.contains(item)
+    }
+
+    /// Returns the logical index of the oldest item matching `predicate`.
+    #[inline]
+    pub fn position<P>(&self, mut predicate: P) -> Option<usize>
+        where P: FnMut(&I) -> bool
+    {
+        let (first, second) = self.as_slices();
+
+        match first.iter().position(&mut predicate) {
+            Some(idx) => Some(idx),
+            None => second.iter().position(predicate).map(|idx| first.len() + idx),
+        }
+    }
+
+    /// Returns the logical index of the oldest occurrence of `needle`.
+    ///
+    /// Matches may straddle the end of the backing store. An empty needle
+    /// matches at index `0`.
+    pub fn find(&self, needle: &[I]) -> Option<usize>
+        where I: PartialEq
+    {
+        if needle.is_empty() {
+            return Some(0);
+        }
+
+        self.windows(needle.len()).position(|(a, b)| {
+            let (head, tail) = needle.split_at(a.len());
+            a == head && b == tail
+        })
+    }
+
+    /// Returns the contents of the buffer as two slices.
+    ///
+    /// Concatenated, the slices hold all items in logical order, oldest
+    /// first. The second slice is empty unless the contents wrap around the
+    /// end of the backing store.
+    #[inline]
+    pub fn as_slices(&self) -> (&[I], &[I]) {
+        if self.is_empty() {
+            return (&[], &[]);
+        }
+
+        let start = self.read_start();
+        let (wrapped, tail) = self.data.as_ref().split_at(start);
+        let first_len = cmp::min(self.len, tail.len());
+
+        (&tail[..first_len], &wrapped[..self.len - first_len])
+    }
+
+    /// Returns the contents of the buffer as two mutable slices.
+    ///
+    /// The slices are laid out like those returned by `as_slices()`.
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> (&mut [I], &mut [I]) {
+        if self.is_empty() {
+            return (&mut [], &mut []);
+        }
+
+        let start = self.read_start();
+        let len = self.len;
+        let (wrapped, tail) = self.data.as_mut().split_at_mut(start);
+        let first_len = cmp::min(len, tail.len());
+
+        (&mut tail[..first_len], &mut wrapped[..len - first_len])
+    }
+
+    /// Clones the oldest items into `dst`, leaving the buffer unchanged.
+    ///
+    /// Copies `min(dst.len(), len())` items and returns their number.
+    pub fn peek_into(&self, dst: &mut [I]) -> usize
+        where I: Clone
+    {
+        let (first, second) = self.as_slices();
+        let a = cmp::min(first.len(), dst.len());
+        let b = cmp::min(second.len(), dst.len() - a);
+
+        dst[..a].clone_from_slice(&first[..a]);
+        dst[a..a + b].clone_from_slice(&second[..b]);
+        a + b
+    }
+
+    /// Sequence number of the oldest item.
+    #[inline]
+    fn seq_start(&self) -> usize {
+        self.seq - self.len
+    }
+
+    /// Sequence number of the next push.
+    #[inline]
+    fn seq_end(&self) -> usize {
+        self.seq
+    }
+
+    /// Records that the items before the oldest one were overwritten.
+    ///
+    /// Without knowing what the last of them was, it may have been in the
+    /// middle of a line.
+    #[inline]
+    fn mark_overwritten(&mut self) {
+        self.overwritten = self.seq_start();
+        self.line_cut = true;
+    }
+
+    /// The item that pushing `count` more items, yielded by `items`, would
+    /// overwrite last, if any.
+    fn last_overwritten_by<J>(&self, count: usize, mut items: J) -> Option<I>
+        where J: Iterator<Item = I>,
+              I: Clone
+    {
+        if self.overflow != Overflow::Overwrite || self.capacity() == 0 {
+            return None;
+        }
+
+        match (self.len + count).saturating_sub(self.capacity()) {
+            0 => None,
+            n if n <= self.len => self.get(n - 1).cloned(),
+            n => items.nth(n - self.len - 1),
+        }
+    }
+
+    #[inline]
+    fn read_start(&self) -> usize {
+        self.wrap(self.pos + self.capacity() - self.len)
+    }
+
+    /// Pushes `item`, returning the previous contents of its slot instead
+    /// of dropping them, or `Err(item)` if the overflow policy rejects it.
+    ///
+    /// The previous contents are the evicted oldest item if the buffer was
+    /// full, and a stale value otherwise.
+    #[inline]
+    fn replace_next(&mut self, item: I) -> Result<I, I> {
+        let full = self.is_full();
+        if full && (self.overflow != Overflow::Overwrite || self.len == 0) {
+            return Err(item);
+        }
+
+        let old = mem::replace(&mut self.data.as_mut()[self.pos], item);
+        self.total += 1;
+        self.seq += 1;
+        self.len += !full as usize;
+        self.pos = self.next_pos();
+        if full {
+            self.mark_overwritten();
+        }
+        Ok(old)
+    }
+
+    /// Insert position after the next push.
+    ///
+    /// Wraps by comparison rather than division, as the position only ever
+    /// steps by one.
+    #[inline]
+    fn next_pos(&self) -> usize {
+        let next = self.pos + 1;
+        if next == self.capacity() { 0 } else { next }
+    }
+
+    /// Reduces position `idx` modulo the capacity.
+    #[inline]
+    fn wrap(&self, idx: usize) -> usize {
+        match self.mask {
+            UNKNOWN_MASK => wrap(idx, self.capacity()),
+            NO_MASK => idx % self.capacity(),
+            mask => idx & mask,
+        }
+    }
+
+    /// Translates a logical index into a position in the backing store.
+    #[inline]
+    fn physical(&self, index: usize) -> usize {
+        self.wrap(self.read_start() + index)
+    }
+}
+
+/// Mask of a buffer whose capacity is not a power of two
+const NO_MASK: usize = usize::MAX;
+
+/// Mask of a buffer whose capacity has not been checked
+const UNKNOWN_MASK: usize = usize::MAX - 1;
+
+/// The mask reducing positions modulo `cap`, or `NO_MASK`.
+///
+/// Neither sentinel is of the form `2^k - 1` that a real mask has.
+#[inline]
+const fn mask_for(cap: usize) -> usize {
+    if cap.is_power_of_two() { cap - 1 } else { NO_MASK }
+}
+
+/// Reduces position `idx` modulo the capacity `cap`.
+///
+/// Power-of-two capacities are masked instead of divided, sparing targets
+/// without a hardware divider the division on every push.
+#[inline]
+fn wrap(idx: usize, cap: usize) -> usize {
+    if cap.is_power_of_two() {
+        idx & (cap - 1)
+    } else {
+        idx % cap
+    }
+}
+
+/// Bytes per word scanned by `find_in()` and `rfind_in()`
+const WORD: usize = mem::size_of::<usize>();
+
+/// A word with every byte set to one
+const ONES: usize = usize::MAX / 0xff;
+
+/// A word with the high bit of every byte set
+const HIGHS: usize = ONES << 7;
+
+/// Returns `true` if any byte of `word` is zero.
+///
+/// Subtracting one from every byte borrows into the high bit only for
+/// bytes that were zero, or that had it set already, which `!word` masks.
+#[inline]
+fn has_zero_byte(word: usize) -> bool {
+    word.wrapping_sub(ONES) & !word & HIGHS != 0
+}
+
+/// Reads a word from `chunk`, which holds exactly `WORD` bytes.
+#[inline]
+fn read_word(chunk: &[u8]) -> usize {
+    let mut bytes = [0; WORD];
+    bytes.copy_from_slice(chunk);
+    usize::from_ne_bytes(bytes)
+}
+
+/// Index of the first `byte` in `haystack`.
+///
+/// Skips whole words without a match, then finds the byte within the
+/// word that has one.
+fn find_in(haystack: &[u8], byte: u8) -> Option<usize> {
+    let pattern = ONES * byte as usize;
+    let mut start = 0;
+    for chunk in haystack.chunks_exact(WORD) {
+        if has_zero_byte(read_word(chunk) ^ pattern) {
+            break;
+        }
+        start += WORD;
+    }
+
+    haystack[start..].iter().position(|&b| b == byte).map(|idx| start + idx)
+}
+
+/// Index of the last `byte` in `haystack`, see `find_in()`.
+fn rfind_in(haystack: &[u8], byte: u8) -> Option<usize> {
+    let pattern = ONES * byte as usize;
+    let mut end = haystack.len();
+    for chunk in haystack.rchunks_exact(WORD) {
+        if has_zero_byte(read_word(chunk) ^ pattern) {
+            break;
+        }
+        end -= WORD;
+    }
+
+    haystack[..end].iter().rposition(|&b| b == byte)
+}
+
+/// Debug helper listing the logical contents of a buffer.
+struct DebugItems<'a, C, I>(&'a WheelBuf<C, I>)
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a;
+
+impl<'a, C, I> fmt::Debug for DebugItems<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a + fmt::Debug,
+          C: 'a
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.0.iter()).finish()
+    }
+}
+
+impl<C, I> fmt::Debug for WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: fmt::Debug
+{
+    /// Prints the items oldest to newest, along with capacity and length.
+    ///
+    /// The alternate form (`{:#?}`) prints the physical layout instead: the
+    /// raw backing store, including stale slots, and the internal indices.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            f.debug_struct("WheelBuf")
+                .field("data", &self.data.as_ref())
+                .field("pos", &self.pos)
+                .field("len", &self.len)
+                .field("total", &self.total)
+                .field("seq", &self.seq)
+                .field("overwritten", &self.overwritten)
+                .field("overflow", &self.overflow)
+                .finish()
+        } else {
+            f.debug_struct("WheelBuf")
+                .field("capacity", &self.capacity())
+                .field("len", &self.len)
+                .field("items", &DebugItems(self))
+                .finish()
+        }
+    }
+}
+
+impl<C, I> Clone for WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]> + Clone
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        WheelBuf {
+            data: self.data.clone(),
+            pos: self.pos,
+            len: self.len,
+            total: self.total,
+            seq: self.seq,
+            overwritten: self.overwritten,
+            line_cut: self.line_cut,
+            mask: self.mask,
+            overflow: self.overflow,
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<I, const N: usize> WheelBuf<[I; N], I> {
+    /// Capacity of the buffer, known at compile time.
+    pub const CAPACITY: usize = N;
+
+    const NONZERO: () = assert!(N > 0, "WheelBuf capacity must be non-zero");
+
+    /// Creates a new WheelBuf backed by `data`.
+    ///
+    /// Unlike `new()`, fails to compile if `N` is zero.
+    #[inline]
+    pub const fn from_array(data: [I; N]) -> Self {
+        let () = Self::NONZERO;
+        let mut wheel = WheelBuf::new(data);
+        wheel.mask = mask_for(N);
+        wheel
+    }
+}
+
+impl<I, const N: usize> Default for WheelBuf<[I; N], I>
+    where I: Default
+{
+    /// Creates an empty buffer backed by an array of `I::default()` items.
+    #[inline]
+    fn default() -> Self {
+        WheelBuf::from_store(core::array::from_fn(|_| I::default()))
+    }
+}
+
+impl<C, D, I, J> PartialEq<WheelBuf<D, J>> for WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          D: AsMut<[J]> + AsRef<[J]>,
+          I: PartialEq<J>
+{
+    /// Compares the logical contents, oldest first.
+    ///
+    /// Capacity, backing type and insert position are not taken into
+    /// account.
+    #[inline]
+    fn eq(&self, other: &WheelBuf<D, J>) -> bool {
+        self.len == other.len && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<C, I> Eq for WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Eq
+{
+}
+
+impl<C, I> Hash for WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Hash
+{
+    /// Hashes the logical contents, consistent with `PartialEq`.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for item in self.iter() {
+            item.hash(state);
+        }
+    }
+}
+
+impl<'a, C, I> Iterator for WheelBufIter<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+    type Item = &'a I;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur >= self.end {
+            return None;
+        }
+
+        let cur = self.cur;
+        self.cur += 1;
+        self.buffer.get(cur)
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.cur += cmp::min(n, self.end - self.cur);
+
+        self.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.cur;
+        (remaining, Some(remaining))
+    }
+
+    // `try_fold` would profit just as much, but cannot be overridden on
+    // stable, as its signature names the unstable `Try` trait.
+    #[inline]
+    fn fold<B, F>(self, init: B, mut f: F) -> B
+        where F: FnMut(B, Self::Item) -> B
+    {
+        let (first, second) = self.remaining();
+        let acc = first.iter().fold(init, &mut f);
+        second.iter().fold(acc, f)
+    }
+}
+
+impl<'a, C, I> WheelBufIter<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    /// The items not yet yielded, as two contiguous slices in order.
+    fn remaining(&self) -> (&'a [I], &'a [I]) {
+        let (first, second) = self.buffer.as_slices();
+        let split = first.len();
+
+        if self.end <= split {
+            (&first[self.cur..self.end], &[])
+        } else if self.cur >= split {
+            (&[], &second[self.cur - split..self.end - split])
+        } else {
+            (&first[self.cur..], &second[..self.end - split])
+        }
+    }
+}
+
+impl<'a, C, I> ExactSizeIterator for WheelBufIter<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+}
+
+impl<'a, C, I> FusedIterator for WheelBufIter<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+}
+
+impl<'a, C, I> DoubleEndedIterator for WheelBufIter<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.cur >= self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        self.buffer.get(self.end)
+    }
+
+    #[inline]
+    fn rfold<B, F>(self, init: B, mut f: F) -> B
+        where F: FnMut(B, Self::Item) -> B
+    {
+        let (first, second) = self.remaining();
+        let acc = second.iter().rfold(init, &mut f);
+        first.iter().rfold(acc, f)
+    }
+}
+
+impl<'a, C, I> Iterator for WheelBufIndexed<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+    type Item = (usize, &'a I);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let seq = self.seq_start + self.iter.cur;
+        self.iter.next().map(|item| (seq, item))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, C, I> DoubleEndedIterator for WheelBufIndexed<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next_back()?;
+        Some((self.seq_start + self.iter.end, item))
+    }
+}
+
+impl<'a, C, I> ExactSizeIterator for WheelBufIndexed<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+}
+
+impl<'a, C, I> FusedIterator for WheelBufIndexed<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+}
+
+impl<'a, I> Iterator for WheelBufIterMut<'a, I>
+    where I: 'a
+{
+    type Item = &'a mut I;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.first.next() {
+            Some(item) => Some(item),
+            None => self.second.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.first.len() + self.second.len();
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, I> ExactSizeIterator for WheelBufIterMut<'a, I>
+    where I: 'a
+{
+}
+
+impl<'a, I> FusedIterator for WheelBufIterMut<'a, I>
+    where I: 'a
+{
+}
+
+impl<'a, I> DoubleEndedIterator for WheelBufIterMut<'a, I>
+    where I: 'a
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.second.next_back() {
+            Some(item) => Some(item),
+            None => self.first.next_back(),
+        }
+    }
+}
+
+impl<C, I> Iterator for WheelBufIntoIter<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Default
+{
+    type Item = I;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.pop_front()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.buffer.len(), Some(self.buffer.len()))
+    }
+}
+
+impl<C, I> ExactSizeIterator for WheelBufIntoIter<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Default
+{
+}
+
+impl<C, I> FusedIterator for WheelBufIntoIter<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Default
+{
+}
+
+impl<C, I> DoubleEndedIterator for WheelBufIntoIter<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Default
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.buffer.pop_back()
+    }
+}
+
+impl<'a, C, I> Iterator for WheelBufDrain<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a + Default,
+          C: 'a
+{
+    type Item = I;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur >= self.end {
+            return None;
+        }
+
+        let idx = self.buffer.physical(self.cur);
+        self.cur += 1;
+        Some(mem::take(&mut self.buffer.data.as_mut()[idx]))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.cur;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, C, I> DoubleEndedIterator for WheelBufDrain<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a + Default,
+          C: 'a
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.cur >= self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        let idx = self.buffer.physical(self.end);
+        Some(mem::take(&mut self.buffer.data.as_mut()[idx]))
+    }
+}
+
+impl<'a, C, I> ExactSizeIterator for WheelBufDrain<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a + Default,
+          C: 'a
+{
+}
+
+impl<'a, C, I> FusedIterator for WheelBufDrain<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a + Default,
+          C: 'a
+{
+}
+
+impl<'a, C, I> Drop for WheelBufDrain<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a + Default,
+          C: 'a
+{
+    fn drop(&mut self) {
+        for _ in self.by_ref() {}
+        self.buffer.close_gap(self.start, self.drain_end - self.start);
+    }
+}
+
+impl<C, I> IntoIterator for WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Default
+{
+    type Item = I;
+    type IntoIter = WheelBufIntoIter<C, I>;
+
+    /// Consumes the buffer, yielding its items by value in logical order.
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        WheelBufIntoIter { buffer: self }
+    }
+}
+
+impl<'a, C, I> IntoIterator for &'a WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    type Item = &'a I;
+    type IntoIter = WheelBufIter<'a, C, I>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, C, I> IntoIterator for &'a mut WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    type Item = &'a mut I;
+    type IntoIter = WheelBufIterMut<'a, I>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<C, I> Extend<I> for WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    /// Pushes every item of `iter`, overwriting the oldest items once full.
+    #[inline]
+    fn extend<T: IntoIterator<Item = I>>(&mut self, iter: T) {
+        for item in iter {
+            self.push(item)
+        }
+    }
+}
+
+impl<C, I> Index<usize> for WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    type Output = I;
+
+    #[inline]
+    fn index(&self, index: usize) -> &I {
+        let len = self.len;
+        match self.get(index) {
+            Some(item) => item,
+            None => panic!("index out of bounds: the len is {} but the index is {}", len, index),
+        }
+    }
+}
+
+impl<C, I> IndexMut<usize> for WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut I {
+        let len = self.len;
+        match self.get_mut(index) {
+            Some(item) => item,
+            None => panic!("index out of bounds: the len is {} but the index is {}", len, index),
+        }
+    }
+}
+
+impl<C> fmt::Display for WheelBuf<C, char>
+    where C: AsMut<[char]> + AsRef<[char]>
+{
+    /// Writes the buffered characters, oldest first.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.iter() {
+            f.write_char(*c)?;
+        }
+        Ok(())
+    }
+}
+
+impl<C> Write for WheelBuf<C, char>
+    where C: AsMut<[char]> + AsRef<[char]>
+{
+    fn write_str(&mut self, s: &str) -> Result<(), core::fmt::Error> {
+        let mut s = s;
+        let mut last = None;
+        if self.overflow == Overflow::Overwrite {
+            if self.capacity() == 0 {
+                return Ok(());
+            }
+
+            let count = s.chars().count();
+            last = self.last_overwritten_by(count, s.chars());
+            if count > self.capacity() {
+                let skip = count - self.capacity();
+                let (at, _) = s.char_indices().nth(skip).unwrap();
+                self.total += skip;
+                self.seq += skip;
+                self.pos = self.wrap(self.pos + skip);
+                s = &s[at..];
+            }
+        }
+
+        for c in s.chars() {
+            if self.push_evict(c).is_some() && self.overflow == Overflow::Reject {
+                return Err(core::fmt::Error);
+            }
+        }
+
+        if let Some(c) = last {
+            self.mark_overwritten();
+            self.line_cut = c != '\n';
+        }
+        Ok(())
+    }
+}
+
+impl<C> WheelBuf<C, u8>
+    where C: AsMut<[u8]> + AsRef<[u8]>
+{
+    /// Returns the logical index of the oldest occurrence of `byte`.
+    ///
+    /// Scans the two contiguous regions of the buffer a word at a time.
+    #[inline]
+    pub fn find_byte(&self, byte: u8) -> Option<usize> {
+        let (first, second) = self.as_slices();
+
+        match find_in(first, byte) {
+            Some(idx) => Some(idx),
+            None => find_in(second, byte).map(|idx| first.len() + idx),
+        }
+    }
+
+    /// Returns the logical index of the newest occurrence of `byte`.
+    #[inline]
+    pub fn rfind_byte(&self, byte: u8) -> Option<usize> {
+        let (first, second) = self.as_slices();
+
+        match rfind_in(second, byte) {
+            Some(idx) => Some(first.len() + idx),
+            None => rfind_in(first, byte),
+        }
+    }
+
+    /// Pushes `bytes` like `extend_from_slice()`, noting whether the last
+    /// overwritten byte ended a line.
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        let last = self.last_overwritten_by(bytes.len(), bytes.iter().cloned());
+        self.extend_from_slice(bytes);
+
+        if let Some(b) = last {
+            self.line_cut = b != b'\n';
+        }
+    }
+}
+
+impl<C> Write for WheelBuf<C, u8>
+    where C: AsMut<[u8]> + AsRef<[u8]>
+{
+    /// Pushes the UTF-8 encoding of `s`.
+    ///
+    /// If the buffer runs out of room under `Saturate` or `Reject`, only
+    /// whole characters are written. Overwriting may still leave a partial
+    /// character at the oldest end of the buffer.
+    fn write_str(&mut self, s: &str) -> Result<(), core::fmt::Error> {
+        if self.overflow == Overflow::Overwrite {
+            self.write_bytes(s.as_bytes());
+            return Ok(());
+        }
+
+        let mut n = cmp::min(s.len(), self.capacity() - self.len);
+        while !s.is_char_boundary(n) {
+            n -= 1;
+        }
+        self.extend_from_slice(&s.as_bytes()[..n]);
+
+        if n < s.len() && self.overflow == Overflow::Reject {
+            Err(core::fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+#[macro_use]
+extern crate std;
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+    use std::string::String;
+    use std::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn basics() {
+        let mut buf = ['x'; 8];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.push('H');
+        wheel.push('e');
+        wheel.push('l');
+        assert_eq!(wheel.len(), 3);
+        assert_eq!(*wheel.iter().next().unwrap(), 'H');
+
+        wheel.push('l');
+        wheel.push('o');
+        wheel.push(' ');
+        wheel.push('W');
+        wheel.push('o');
+        wheel.push('r');
+        wheel.push('l');
+        wheel.push('d');
+        assert_eq!(wheel.len(), 8);
+
+        let s: String = wheel.iter().cloned().collect();
+        assert_eq!(s.as_str(), "lo World");
+    }
+
+    #[test]
+    #[allow(clippy::iter_nth_zero)]
+    fn nth() {
+        let mut buf = ['x'; 8];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.push('H');
+        wheel.push('e');
+        wheel.push('l');
+
+        assert_eq!(*wheel.iter().nth(0).unwrap(), 'H');
+        assert_eq!(*wheel.iter().nth(1).unwrap(), 'e');
+        assert_eq!(*wheel.iter().nth(2).unwrap(), 'l');
+        assert!(wheel.iter().nth(3).is_none());
+    }
+
+    #[test]
+    fn front_back() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert!(wheel.front().is_none());
+        assert!(wheel.back().is_none());
+
+        write!(wheel, "Hello").unwrap();
+        assert_eq!(wheel.front(), Some(&'e'));
+        assert_eq!(wheel.back(), Some(&'o'));
+    }
+
+    #[test]
+    fn clear() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        write!(wheel, "Hello").unwrap();
+        wheel.clear();
+        assert_eq!(wheel.len(), 0);
+        assert_eq!(wheel.total(), 5);
+        assert!(wheel.iter().next().is_none());
+
+        wheel.push('a');
+        assert_eq!(wheel.front(), Some(&'a'));
+
+        wheel.clear_drop();
+        assert_eq!(wheel.len(), 0);
+        assert_eq!(buf, ['\0', 'e', 'l', 'l']);
+    }
+
+    #[test]
+    fn empty_full() {
+        let mut buf = ['x'; 2];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert!(wheel.is_empty());
+        assert!(!wheel.is_full());
+
+        wheel.push('a');
+        assert!(!wheel.is_empty());
+        assert!(!wheel.is_full());
+
+        wheel.push('b');
+        assert!(wheel.is_full());
+    }
+
+    #[test]
+    fn get() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert!(wheel.get(0).is_none());
+
+        write!(wheel, "Hello").unwrap();
+        assert_eq!(wheel.get(0), Some(&'e'));
+        assert_eq!(wheel.get(2), Some(&'l'));
+        assert_eq!(wheel.get(3), Some(&'o'));
+        assert!(wheel.get(4).is_none());
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut buf = [0; 3];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert!(wheel.get_mut(0).is_none());
+
+        for i in 1..5 {
+            wheel.push(i);
+        }
+        *wheel.get_mut(2).unwrap() += 10;
+        assert!(wheel.get_mut(3).is_none());
+
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [2, 3, 14]);
+    }
+
+    #[test]
+    fn index() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        write!(wheel, "Hello").unwrap();
+        assert_eq!(wheel[0], 'e');
+        wheel[0] = 'a';
+        assert_eq!(wheel[0], 'a');
+        assert_eq!(wheel[3], 'o');
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.push('a');
+        let _ = wheel[1];
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut buf = [0; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        for i in 0..6 {
+            wheel.push(i);
+        }
+        for item in wheel.iter_mut() {
+            *item *= 10;
+        }
+
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [20, 30, 40, 50]);
+    }
+
+    #[test]
+    fn seq() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert!(wheel.first_seq().is_none());
+        assert!(wheel.last_seq().is_none());
+
+        write!(wheel, "Hello").unwrap();
+        assert_eq!(wheel.first_seq(), Some(1));
+        assert_eq!(wheel.last_seq(), Some(4));
+        assert_eq!(wheel.get_seq(4), Some(&'o'));
+        assert!(wheel.get_seq(0).is_none());
+        assert!(wheel.get_seq(5).is_none());
+
+        *wheel.get_seq_mut(1).unwrap() = 'E';
+        wheel.push('!');
+        assert!(wheel.get_seq(1).is_none());
+        assert_eq!(wheel.get_seq(5), Some(&'!'));
+    }
+
+    #[test]
+    fn seq_never_reused() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        wheel.extend(1..4);
+        assert_eq!(wheel.pop_back(), Some(3));
+        assert_eq!(wheel.first_seq(), Some(3));
+        assert!(wheel.get_seq(0).is_none());
+        assert!(wheel.get_seq(2).is_none());
+
+        wheel.push(4);
+        assert_eq!(wheel.get_seq(5), Some(&4));
+
+        wheel.insert(1, 9);
+        assert_eq!(wheel.first_seq(), Some(7));
+        assert!(wheel.get_seq(6).is_none());
+
+        // Pushes and removals from the front keep the numbers.
+        wheel.pop_front();
+        wheel.push(5);
+        assert_eq!(wheel.first_seq(), Some(8));
+        assert_eq!(wheel.get_seq(10), Some(&4));
+        assert_eq!(wheel.get_seq(11), Some(&5));
+    }
+
+    #[test]
+    fn latest() {
+        let mut buf = ['x'; 8];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        write!(wheel, "Hello, World").unwrap();
+        let s: String = wheel.latest(3).cloned().collect();
+        assert_eq!(s.as_str(), "rld");
+        let s: String = wheel.latest(3).rev().cloned().collect();
+        assert_eq!(s.as_str(), "dlr");
+        assert_eq!(wheel.latest(20).len(), 8);
+        assert_eq!(wheel.latest(0).len(), 0);
+    }
+
+    #[test]
+    fn iter_indexed() {
+        let mut buf = ['x'; 3];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        write!(wheel, "abcde").unwrap();
+        let v: Vec<(usize, char)> = wheel.iter_indexed().map(|(i, c)| (i, *c)).collect();
+        assert_eq!(v, [(2, 'c'), (3, 'd'), (4, 'e')]);
+
+        let mut iter = wheel.iter_indexed();
+        assert_eq!(iter.next_back(), Some((4, &'e')));
+        assert_eq!(iter.len(), 2);
+    }
+
+    #[test]
+    fn iter_from() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        wheel.extend(0..6);
+
+        let v: Vec<i32> = wheel.iter_from(4).cloned().collect();
+        assert_eq!(v, [4, 5]);
+        let v: Vec<i32> = wheel.iter_from(0).cloned().collect();
+        assert_eq!(v, [2, 3, 4, 5]);
+        assert_eq!(wheel.iter_from(6).count(), 0);
+        assert_eq!(wheel.iter_from(100).count(), 0);
+    }
+
+    #[test]
+    fn iter_step() {
+        let mut wheel = WheelBuf::new([0; 5]);
+        wheel.extend(0..8);
+
+        let v: Vec<i32> = wheel.iter_step(2).cloned().collect();
+        assert_eq!(v, [3, 5, 7]);
+        let v: Vec<i32> = wheel.iter_step(3).rev().cloned().collect();
+        assert_eq!(v, [6, 3]);
+        assert_eq!(wheel.iter_step(10).len(), 1);
+    }
+
+    #[test]
+    fn pop_front() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert!(wheel.pop_front().is_none());
+
+        write!(wheel, "Hello").unwrap();
+        assert_eq!(wheel.pop_front(), Some('e'));
+        assert_eq!(wheel.pop_front(), Some('l'));
+        assert_eq!(wheel.len(), 2);
+        assert_eq!(wheel.total(), 5);
+
+        wheel.push('!');
+        let s: String = wheel.iter().cloned().collect();
+        assert_eq!(s.as_str(), "lo!");
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut wheel = WheelBuf::new(vec![String::new(), String::new(), String::new()]);
+
+        for word in &["a", "b", "c", "d"] {
+            wheel.push(String::from(*word));
+        }
+
+        let v: Vec<String> = wheel.into_iter().collect();
+        assert_eq!(v, ["b", "c", "d"]);
+    }
+
+    #[test]
+    fn into_iter_ref() {
+        let mut buf = [0; 3];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        for i in 0..4 {
+            wheel.push(i);
+        }
+        for item in &mut wheel {
+            *item += 1;
+        }
+
+        let mut v = Vec::new();
+        for item in &wheel {
+            v.push(*item);
+        }
+        assert_eq!(v, [2, 3, 4]);
+    }
+
+    #[test]
+    fn push_evict() {
+        let mut buf = ['x'; 2];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert!(wheel.push_evict('a').is_none());
+        assert!(wheel.push_evict('b').is_none());
+        assert_eq!(wheel.push_evict('c'), Some('a'));
+        assert_eq!(wheel.push_evict('d'), Some('b'));
+        assert_eq!(wheel.len(), 2);
+    }
+
+    #[test]
+    fn try_push() {
+        let mut buf = ['x'; 2];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert_eq!(wheel.try_push('a'), Ok(()));
+        assert_eq!(wheel.try_push('b'), Ok(()));
+        assert_eq!(wheel.try_push('c'), Err(PushError('c')));
+
+        let s: String = wheel.iter().cloned().collect();
+        assert_eq!(s.as_str(), "ab");
+    }
+
+    #[test]
+    fn overflow() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert_eq!(wheel.overflow(), Overflow::Overwrite);
+        wheel.set_overflow(Overflow::Saturate);
+        write!(wheel, "Hello").unwrap();
+        assert_eq!(wheel.push_evict('!'), Some('!'));
+
+        let s: String = wheel.iter().cloned().collect();
+        assert_eq!(s.as_str(), "Hell");
+
+        wheel.set_overflow(Overflow::Reject);
+        assert!(write!(wheel, "o").is_err());
+        wheel.pop_back();
+        assert!(write!(wheel, "p").is_ok());
+    }
+
+    #[test]
+    fn pop_back() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert!(wheel.pop_back().is_none());
+
+        write!(wheel, "Hello").unwrap();
+        assert_eq!(wheel.pop_back(), Some('o'));
+        assert_eq!(wheel.pop_back(), Some('l'));
+        assert_eq!(wheel.len(), 2);
+        assert_eq!(wheel.total(), 3);
+
+        wheel.push('p');
+        let s: String = wheel.iter().cloned().collect();
+        assert_eq!(s.as_str(), "elp");
+    }
+
+    #[test]
+    fn rev() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        write!(wheel, "Hello").unwrap();
+        let s: String = wheel.iter().rev().collect();
+        assert_eq!(s.as_str(), "olle");
+
+        let mut iter = wheel.iter();
+        assert_eq!(iter.next(), Some(&'e'));
+        assert_eq!(iter.next_back(), Some(&'o'));
+        assert_eq!(iter.next_back(), Some(&'l'));
+        assert_eq!(iter.next(), Some(&'l'));
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+
+        let s: String = wheel.iter_mut().rev().map(|c| *c).collect();
+        assert_eq!(s.as_str(), "olle");
+    }
+
+    #[test]
+    fn exact_size() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        write!(wheel, "Hello").unwrap();
+        let mut iter = wheel.iter();
+        assert_eq!(iter.len(), 4);
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        iter.nth(5);
+        assert_eq!(iter.len(), 0);
+        assert!(iter.next().is_none());
+        assert!(iter.next().is_none());
+
+        let mut iter = wheel.iter_mut();
+        iter.next();
+        assert_eq!(iter.len(), 3);
+    }
+
+    #[test]
+    fn extend() {
+        let mut buf = [0; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend(0..3);
+        wheel.extend(vec![10, 11, 12]);
+
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [2, 10, 11, 12]);
+    }
+
+    #[test]
+    fn extend_from_slice() {
+        let mut buf = [0; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend_from_slice(&[1, 2, 3]);
+        wheel.extend_from_slice(&[4, 5]);
+        assert_eq!(wheel.total(), 5);
+
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [2, 3, 4, 5]);
+
+        wheel.extend_from_slice(&[6, 7, 8, 9, 10, 11]);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [8, 9, 10, 11]);
+
+        wheel.pop_back();
+        wheel.set_overflow(Overflow::Saturate);
+        wheel.extend_from_slice(&[12, 13]);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [8, 9, 10, 12]);
+    }
+
+    #[test]
+    fn as_slices() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        assert_eq!(wheel.as_slices(), (&[][..], &[][..]));
+
+        write!(wheel, "Hel").unwrap();
+        assert_eq!(wheel.as_slices(), (&['H', 'e', 'l'][..], &[][..]));
+
+        write!(wheel, "lo").unwrap();
+        assert_eq!(wheel.as_slices(), (&['e', 'l', 'l'][..], &['o'][..]));
+    }
+
+    #[test]
+    fn as_mut_slices() {
+        let mut buf = [0; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend(1..6);
+        {
+            let (first, second) = wheel.as_mut_slices();
+            assert_eq!(first, &[2, 3, 4]);
+            assert_eq!(second, &[5]);
+
+            for x in first.iter_mut().chain(second.iter_mut()) {
+                *x *= 2;
+            }
+        }
+
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [4, 6, 8, 10]);
+    }
+
+    #[test]
+    fn make_contiguous() {
+        let mut buf = [0; 5];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend(1..4);
+        assert_eq!(wheel.make_contiguous(), &[1, 2, 3]);
+
+        wheel.extend(4..8);
+        assert_eq!(wheel.make_contiguous(), &[3, 4, 5, 6, 7]);
+        assert_eq!(wheel.as_slices(), (&[3, 4, 5, 6, 7][..], &[][..]));
+
+        wheel.pop_front();
+        wheel.pop_front();
+        wheel.extend(8..10);
+        assert_eq!(wheel.make_contiguous(), &[5, 6, 7, 8, 9]);
+
+        wheel.pop_back();
+        wheel.push(10);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [5, 6, 7, 8, 10]);
+    }
+
+    #[test]
+    fn contains_position() {
+        let mut buf = ['x'; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        write!(wheel, "Hello").unwrap();
+        assert!(wheel.contains(&'o'));
+        assert!(!wheel.contains(&'H'));
+        assert!(!wheel.contains(&'x'));
+
+        assert_eq!(wheel.position(|&c| c == 'l'), Some(1));
+        assert_eq!(wheel.position(|&c| c == 'o'), Some(3));
+        assert_eq!(wheel.position(|&c| c == 'H'), None);
+    }
+
+    #[test]
+    fn retain() {
+        let mut buf = [0; 5];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend(1..9);
+        wheel.retain(|&x| x % 2 == 0);
+
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [4, 6, 8]);
+
+        wheel.push(9);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [4, 6, 8, 9]);
+
+        wheel.retain(|_| false);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn retain_drops() {
+        use std::rc::Rc;
+
+        let item = Rc::new(());
+        let mut wheel = WheelBuf::new([None, None, None]);
+        wheel.extend((0..4).map(|_| Some(item.clone())));
+        assert_eq!(Rc::strong_count(&item), 4);
+
+        let mut keep = true;
+        wheel.retain(|_| {
+            keep = !keep;
+            keep
+        });
+        assert_eq!(wheel.len(), 1);
+        assert_eq!(Rc::strong_count(&item), 2);
+    }
+
+    #[test]
+    fn retain_zero_capacity() {
+        let mut wheel = WheelBuf::new([0; 0]);
+        wheel.retain(|_| false);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn truncate() {
+        let mut buf = [0; 5];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend(1..8);
+        wheel.truncate_front(4);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [4, 5, 6, 7]);
+
+        wheel.truncate_back(2);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [4, 5]);
+        assert_eq!(wheel.total(), 5);
+
+        wheel.truncate_back(3);
+        wheel.truncate_front(3);
+        assert_eq!(wheel.len(), 2);
+
+        wheel.push(8);
+        assert_eq!(wheel.back(), Some(&8));
+    }
+
+    #[test]
+    fn drain() {
+        let mut buf = [0; 6];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend(1..9);
+        let v: Vec<i32> = wheel.drain(1..3).collect();
+        assert_eq!(v, [4, 5]);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [3, 6, 7, 8]);
+
+        {
+            let mut drain = wheel.drain(2..);
+            assert_eq!(drain.len(), 2);
+            assert_eq!(drain.next_back(), Some(8));
+        }
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [3, 6]);
+
+        wheel.extend(9..12);
+        let v: Vec<i32> = wheel.drain(..=1).collect();
+        assert_eq!(v, [3, 6]);
+        let v: Vec<i32> = wheel.drain(..).collect();
+        assert_eq!(v, [9, 10, 11]);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_out_of_bounds() {
+        let mut buf = [0; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.push(1);
+        wheel.drain(..2);
+    }
+
+    #[test]
+    #[should_panic(expected = "maximum usize")]
+    fn drain_max_bound() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        wheel.drain((Bound::Excluded(usize::MAX), Bound::Unbounded));
+    }
+
+    #[test]
+    fn remove() {
+        let mut buf = [0; 6];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend(1..9);
+        assert_eq!(wheel.remove(1), Some(4));
+        assert_eq!(wheel.remove(3), Some(7));
+        assert_eq!(wheel.remove(4), None);
+
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [3, 5, 6, 8]);
+
+        wheel.extend(9..11);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [3, 5, 6, 8, 9, 10]);
+    }
+
+    #[test]
+    fn insert() {
+        let mut buf = ['x'; 5];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        write!(wheel, "ace").unwrap();
+        assert_eq!(wheel.insert(1, 'b'), None);
+        assert_eq!(wheel.insert(3, 'd'), None);
+        assert_eq!(wheel.total(), 5);
+
+        let s: String = wheel.iter().cloned().collect();
+        assert_eq!(s.as_str(), "abcde");
+
+        assert_eq!(wheel.insert(0, 'z'), Some('z'));
+        assert_eq!(wheel.insert(2, 'X'), Some('a'));
+        assert_eq!(wheel.insert(5, 'f'), Some('b'));
+
+        let s: String = wheel.iter().cloned().collect();
+        assert_eq!(s.as_str(), "Xcdef");
+
+        wheel.set_overflow(Overflow::Saturate);
+        assert_eq!(wheel.insert(2, 'Y'), Some('Y'));
+    }
+
+    #[test]
+    fn swap() {
+        let mut buf = [0; 4];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend(1..7);
+        wheel.swap(0, 3);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [6, 4, 5, 3]);
+
+        assert_eq!(wheel.swap_remove_back(0), Some(6));
+        assert_eq!(wheel.swap_remove_back(3), None);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [3, 4, 5]);
+
+        wheel.push(7);
+        assert_eq!(wheel.back(), Some(&7));
+    }
+
+    #[test]
+    fn rotate() {
+        let mut buf = [0; 6];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend(1..5);
+        wheel.rotate_left(1);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [2, 3, 4, 1]);
+
+        wheel.rotate_left(3);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [1, 2, 3, 4]);
+
+        wheel.rotate_right(1);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [4, 1, 2, 3]);
+
+        wheel.extend(5..9);
+        wheel.rotate_right(2);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [7, 8, 2, 3, 5, 6]);
+        wheel.rotate_left(6);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [7, 8, 2, 3, 5, 6]);
+    }
+
+    #[test]
+    fn fill() {
+        let mut buf = [0; 3];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.push(1);
+        wheel.fill(7);
+        assert!(wheel.is_full());
+        assert_eq!(wheel.total(), 4);
+
+        wheel.push(8);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [7, 7, 8]);
+    }
+
+    #[test]
+    fn clone() {
+        let mut wheel = WheelBuf::new([0; 3]);
+
+        wheel.extend(1..5);
+        let snapshot = wheel.clone();
+        wheel.push(5);
+
+        let v: Vec<i32> = snapshot.iter().cloned().collect();
+        assert_eq!(v, [2, 3, 4]);
+        assert_eq!(snapshot.total(), 4);
+    }
+
+    #[test]
+    fn eq_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash<T: Hash>(t: &T) -> u64 {
+            let mut h = DefaultHasher::new();
+            t.hash(&mut h);
+            h.finish()
+        }
+
+        let mut a = WheelBuf::new([0; 3]);
+        let mut b = WheelBuf::new(vec![0; 5]);
+
+        a.extend(1..5);
+        b.extend(2..5);
+        assert!(a == b);
+        assert_eq!(hash(&a), hash(&b));
+
+        b.push(5);
+        assert!(a != b);
+        a.push(5);
+        b.pop_front();
+        assert!(a == b);
+        assert_eq!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    fn new_full() {
+        let mut wheel = WheelBuf::new_full([1, 2, 3]);
+        assert!(wheel.is_full());
+        assert_eq!(wheel.total(), 3);
+
+        wheel.push(4);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [2, 3, 4]);
+
+        let mut wheel = WheelBuf::new_with_len([1, 2, 0, 0], 2);
+        assert_eq!(wheel.len(), 2);
+        wheel.push(3);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [1, 2, 3]);
+    }
+
+    #[test]
+    fn raw_parts() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        wheel.extend(1..7);
+
+        let parts = wheel.into_raw_parts();
+        assert_eq!(parts, RawParts { data: [5, 6, 3, 4], pos: 2, len: 4, total: 6 });
+
+        let wheel = WheelBuf::from_raw_parts(parts).unwrap();
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [3, 4, 5, 6]);
+
+        let bad = RawParts { data: [0; 4], pos: 4, len: 0, total: 0 };
+        assert_eq!(WheelBuf::<_, i32>::from_raw_parts(bad).unwrap_err(), RawPartsError(bad));
+        let bad = RawParts { data: [0; 4], pos: 0, len: 5, total: 5 };
+        assert!(WheelBuf::<_, i32>::from_raw_parts(bad).is_err());
+        let bad = RawParts { data: [0; 4], pos: 0, len: 2, total: 1 };
+        assert!(WheelBuf::<_, i32>::from_raw_parts(bad).is_err());
+    }
+
+    #[test]
+    fn into_inner() {
+        let mut wheel = WheelBuf::new(vec![0; 3]);
+        wheel.extend(1..5);
+
+        assert_eq!(wheel.data(), &[4, 2, 3]);
+        wheel.data_mut()[0] = 40;
+        assert_eq!(wheel.back(), Some(&40));
+        assert_eq!(wheel.into_inner(), [40, 2, 3]);
+    }
+
+    #[test]
+    fn const_new() {
+        static LOG: WheelBuf<[u8; 16], u8> = WheelBuf::new([0; 16]);
+
+        assert_eq!(LOG.capacity(), 16);
+        assert!(LOG.is_empty());
+    }
+
+    #[test]
+    fn array_wheel() {
+        const WHEEL: ArrayWheelBuf<u8, 4> = WheelBuf::from_array([0; 4]);
+
+        let mut wheel = WHEEL;
+        assert_eq!(ArrayWheelBuf::<u8, 4>::CAPACITY, 4);
+        wheel.extend_from_slice(b"abcde");
+        assert_eq!(wheel.as_slices(), (&b"bcd"[..], &b"e"[..]));
+    }
+
+    #[test]
+    fn default() {
+        #[derive(Default)]
+        struct Shared {
+            log: WheelBuf<[char; 64], char>,
+        }
+
+        let mut shared = Shared::default();
+        assert_eq!(shared.log.capacity(), 64);
+        assert!(shared.log.is_empty());
+
+        write!(shared.log, "boot").unwrap();
+        assert_eq!(shared.log.len(), 4);
+    }
+
+    #[test]
+    fn debug() {
+        let mut buf = [0; 3];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.extend(1..5);
+        assert_eq!(format!("{:?}", wheel), "WheelBuf { capacity: 3, len: 3, items: [2, 3, 4] }");
+
+        wheel.pop_back();
+        assert_eq!(format!("{:?}", wheel), "WheelBuf { capacity: 3, len: 2, items: [2, 3] }");
+
+        let physical = format!("{:#?}", wheel);
+        assert!(physical.contains("data: [\n        0,\n        2,\n        3,\n    ]"));
+        assert!(physical.contains("pos: 0"));
+    }
+
+    #[test]
+    fn write() {
+        let mut buf = ['x'; 8];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        write!(wheel, "Hello, World! {}", 123).unwrap();
+        let s: String = wheel.iter().cloned().collect();
+        assert_eq!(s.as_str(), "rld! 123");
+    }
+
+    #[test]
+    fn write_zero_capacity() {
+        let mut wheel = WheelBuf::new(['x'; 0]);
+        write!(wheel, "Hello").unwrap();
+        assert!(wheel.is_empty());
+        assert_eq!(wheel.total(), 0);
+
+        let mut bytes = WheelBuf::new([0u8; 0]);
+        write!(bytes, "Hello").unwrap();
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn skip_overwritten() {
+        let mut a = WheelBuf::new([0; 3]);
+        let mut b = WheelBuf::new([0; 3]);
+        a.push(0);
+        b.push(0);
+        let items: Vec<i32> = (1..12).collect();
+        a.extend_from_slice(&items);
+        b.extend(items);
+        assert_eq!(a.data(), b.data());
+        assert_eq!(a.total(), 12);
+        assert_eq!(a, b);
+
+        let mut wheel = WheelBuf::new(['x'; 8]);
+        write!(wheel, "Hello, World! {}", 123).unwrap();
+        wheel.write_str("äöü, Hello again").unwrap();
+        let s: String = wheel.iter().cloned().collect();
+        assert_eq!(s.as_str(), "lo again");
+        assert_eq!(wheel.total(), 33);
+    }
+
+    #[test]
+    fn find() {
+        let mut wheel = WheelBuf::new([0u8; 6]);
+        wheel.extend_from_slice(b"xxsyncs");
+
+        assert_eq!(wheel.as_slices(), (&b"xsync"[..], &b"s"[..]));
+        assert_eq!(wheel.find(b"sync"), Some(1));
+        assert_eq!(wheel.find(b"cs"), Some(4));
+        assert_eq!(wheel.find(b"ss"), None);
+        assert_eq!(wheel.find(b"xsyncs!"), None);
+        assert_eq!(wheel.find(b""), Some(0));
+    }
+
+    #[test]
+    fn find_byte() {
+        let mut wheel = WheelBuf::new([0u8; 5]);
+        wheel.extend_from_slice(b"a;b;c;d");
+
+        assert_eq!(wheel.as_slices(), (&b"b;c"[..], &b";d"[..]));
+        assert_eq!(wheel.find_byte(b';'), Some(1));
+        assert_eq!(wheel.rfind_byte(b';'), Some(3));
+        assert_eq!(wheel.find_byte(b'd'), Some(4));
+        assert_eq!(wheel.rfind_byte(b'c'), Some(2));
+        assert_eq!(wheel.find_byte(b'a'), None);
+
+        // Long enough to be scanned in whole words, with matches in every
+        // byte position.
+        let mut wheel = WheelBuf::new([0u8; 40]);
+        wheel.extend_from_slice(&[0x80; 40]);
+        for i in 0..40 {
+            wheel[i] = 0x81;
+            assert_eq!(wheel.find_byte(0x81), Some(i));
+            assert_eq!(wheel.rfind_byte(0x81), Some(i));
+            assert_eq!(wheel.find_byte(0x01), None);
+            wheel[i] = 0x80;
+        }
+
+        wheel.rotate_left(13);
+        wheel[3] = 0;
+        wheel[35] = 0;
+        assert_eq!(wheel.find_byte(0), Some(3));
+        assert_eq!(wheel.rfind_byte(0), Some(35));
+    }
+
+    #[test]
+    fn peek_into() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        wheel.extend(1..7);
+
+        let mut dst = [0; 3];
+        assert_eq!(wheel.peek_into(&mut dst), 3);
+        assert_eq!(dst, [3, 4, 5]);
+
+        let mut dst = [0; 6];
+        assert_eq!(wheel.peek_into(&mut dst), 4);
+        assert_eq!(dst, [3, 4, 5, 6, 0, 0]);
+        assert_eq!(wheel.len(), 4);
+    }
+
+    #[test]
+    fn write_bytes() {
+        let mut wheel = WheelBuf::new([0u8; 6]);
+
+        write!(wheel, "xä").unwrap();
+        assert_eq!(wheel.as_slices(), (&b"x\xc3\xa4"[..], &[][..]));
+
+        wheel.set_overflow(Overflow::Reject);
+        assert!(write!(wheel, "ßä").is_err());
+        assert_eq!(wheel.len(), 5);
+        assert_eq!(&wheel.data()[3..5], "ß".as_bytes());
+    }
+
+    #[test]
+    fn display() {
+        let mut buf = ['x'; 8];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        write!(wheel, "Hello, World!").unwrap();
+        assert_eq!(format!("[{}]", wheel), "[, World!]");
+    }
+
+    #[test]
+    fn using_vec() {
+        let mut buf = vec!['x', 'x', 'x', 'x', 'x', 'x', 'x', 'x'];
+        let mut wheel = WheelBuf::new(&mut buf);
+
+        wheel.push('H');
+        wheel.push('e');
+        wheel.push('l');
+        assert_eq!(wheel.len(), 3);
+        assert_eq!(*wheel.iter().next().unwrap(), 'H');
+
+        wheel.push('l');
+        wheel.push('o');
+        wheel.push(' ');
+        wheel.push('W');
+        wheel.push('o');
+        wheel.push('r');
+        wheel.push('l');
+        wheel.push('d');
+        assert_eq!(wheel.len(), 8);
 
         let s: String = wheel.iter().cloned().collect();
         assert_eq!(s.as_str(), "lo World");
     }
+
+    #[test]
+    fn wrap_power_of_two() {
+        assert_eq!(wrap(5, 4), 1);
+        assert_eq!(wrap(7, 3), 1);
+
+        let mut buf = [0; 4];
+        let mut wheel = WheelBuf::new(&mut buf[..]);
+        for i in 0..10 {
+            wheel.push(i);
+        }
+        assert_eq!(wheel.as_slices(), (&[6, 7][..], &[8, 9][..]));
+        assert_eq!(wheel.pop_back(), Some(9));
+        assert_eq!(wheel[2], 8);
+    }
+
+    #[test]
+    fn mask() {
+        assert_eq!(WheelBuf::new([0; 4]).mask, UNKNOWN_MASK);
+        assert_eq!(WheelBuf::new_with_len([0; 4], 0).mask, 3);
+        assert_eq!(WheelBuf::new_full([0; 3]).mask, NO_MASK);
+        assert_eq!(WheelBuf::from_array([0; 8]).mask, 7);
+
+        let mut wheel = WheelBuf::new_with_len([0; 0], 0);
+        wheel.rotate_left(0);
+        wheel.rotate_right(0);
+    }
+
+    #[test]
+    fn get_unchecked() {
+        let mut wheel = WheelBuf::new([0; 3]);
+        for i in 1..5 {
+            wheel.push(i);
+        }
+
+        unsafe {
+            assert_eq!(*wheel.get_unchecked(0), 2);
+            *wheel.get_unchecked_mut(2) += 10;
+            assert_eq!(*wheel.get_unchecked(2), 14);
+        }
+    }
+
+    #[test]
+    fn iter_fold() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        for i in 1..7 {
+            wheel.push(i);
+        }
+
+        assert_eq!(wheel.iter().sum::<i32>(), 18);
+        assert_eq!(wheel.iter().max(), Some(&6));
+
+        let mut iter = wheel.iter();
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.fold(0, |acc, &x| acc * 10 + x), 45);
+
+        let mut iter = wheel.iter();
+        iter.nth(2);
+        assert_eq!(iter.rfold(0, |acc, &x| acc * 10 + x), 6);
+        assert_eq!(wheel.iter().rfold(0, |acc, &x| acc * 10 + x), 6543);
+    }
 }
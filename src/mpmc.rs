@@ -0,0 +1,301 @@
+//! Lock-free bounded multi-producer multi-consumer queue.
+//!
+//! Each slot carries a sequence stamp that tells producers and consumers
+//! whether it is free or filled for their current lap around the ring (see
+//! Dmitry Vyukov's bounded MPMC queue). Like the SPSC queue, a full queue
+//! rejects new items.
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{PushError, Storage};
+use super::ordering::{MultiCore, OrderingPolicy};
+use super::padded::CachePadded;
+
+/// A storage slot of an MpmcWheelBuf.
+pub struct Slot<I> {
+    stamp: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<I>>,
+}
+
+impl<I> Slot<I> {
+    /// Creates an empty slot.
+    #[inline]
+    pub const fn new() -> Slot<I> {
+        Slot {
+            stamp: AtomicUsize::new(0),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+// A slot's value is only accessed by the thread that claimed it.
+unsafe impl<I> Sync for Slot<I>
+    where I: Send
+{
+}
+
+impl<I> Default for Slot<I> {
+    #[inline]
+    fn default() -> Slot<I> {
+        Slot::new()
+    }
+}
+
+/// A bounded queue that can be pushed to and popped from by any number of
+/// threads through a shared reference.
+///
+/// The capacity must be a power of two. The slots must be exclusive to the
+/// queue, see `Storage`; the mask derived from their number is fixed at
+/// construction.
+pub struct MpmcWheelBuf<C, I, O = MultiCore>
+    where C: Storage<Slot<I>>,
+          O: OrderingPolicy
+{
+    /// Backend store
+    data: C,
+
+    /// `capacity - 1`, for masking positions into slot indices
+    mask: usize,
+
+    /// Position of the next push
+    head: CachePadded<AtomicUsize>,
+
+    /// Position of the next pop
+    tail: CachePadded<AtomicUsize>,
+
+    _pd: PhantomData<(I, O)>,
+}
+
+unsafe impl<C, I, O> Sync for MpmcWheelBuf<C, I, O>
+    where C: Storage<Slot<I>> + Sync,
+          I: Send,
+          O: OrderingPolicy
+{
+}
+
+impl<C, I> MpmcWheelBuf<C, I>
+    where C: Storage<Slot<I>>
+{
+    /// Creates a new, empty MpmcWheelBuf.
+    ///
+    /// Existing contents of `data` are ignored and never dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `data` is not a power of two.
+    #[inline]
+    pub fn new(data: C) -> MpmcWheelBuf<C, I> {
+        // `MultiCore` is correct on any system.
+        unsafe { MpmcWheelBuf::with_ordering(data) }
+    }
+}
+
+impl<C, I, O> MpmcWheelBuf<C, I, O>
+    where C: Storage<Slot<I>>,
+          O: OrderingPolicy
+{
+    /// Creates a new, empty MpmcWheelBuf using the ordering policy `O`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `data` is not a power of two.
+    ///
+    /// # Safety
+    ///
+    /// With `SingleCore`, the queue must only ever be used from one core.
+    /// Pushing and popping on different cores races on the slot contents.
+    pub unsafe fn with_ordering(data: C) -> MpmcWheelBuf<C, I, O> {
+        let cap = data.slots().len();
+        assert!(cap.is_power_of_two(), "capacity {} is not a power of two", cap);
+
+        for (i, slot) in data.slots().iter().enumerate() {
+            slot.stamp.store(i, Ordering::Relaxed);
+        }
+
+        MpmcWheelBuf {
+            data,
+            mask: cap - 1,
+            head: CachePadded::new(AtomicUsize::new(0)),
+            tail: CachePadded::new(AtomicUsize::new(0)),
+            _pd: PhantomData,
+        }
+    }
+
+    /// Capacity of the buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.mask + 1
+    }
+
+    /// Number of items in buffer.
+    ///
+    /// Only a snapshot while other threads push or pop.
+    #[inline]
+    pub fn len(&self) -> usize {
+        let tail = O::load(&self.tail);
+        let head = O::load(&self.head);
+        head.wrapping_sub(tail)
+    }
+
+    /// Returns `true` if the buffer holds no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add item to the buffer, unless it is full.
+    pub fn push(&self, item: I) -> Result<(), PushError<I>> {
+        let slots = self.data.slots();
+        let mut pos = self.head.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &slots[pos & self.mask];
+            let stamp = O::load(&slot.stamp);
+            let diff = stamp.wrapping_sub(pos) as isize;
+
+            if diff == 0 {
+                match self.head.compare_exchange_weak(pos, pos.wrapping_add(1),
+                                                      Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => {
+                        // Winning the exchange grants exclusive access to the
+                        // slot until its stamp is advanced.
+                        unsafe {
+                            (*slot.value.get()).write(item);
+                        }
+                        O::store(&slot.stamp, pos.wrapping_add(1));
+                        return Ok(());
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                return Err(PushError(item));
+            } else {
+                pos = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Removes the oldest item from the buffer.
+    pub fn pop(&self) -> Option<I> {
+        let slots = self.data.slots();
+        let mut pos = self.tail.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &slots[pos & self.mask];
+            let stamp = O::load(&slot.stamp);
+            let diff = stamp.wrapping_sub(pos.wrapping_add(1)) as isize;
+
+            if diff == 0 {
+                match self.tail.compare_exchange_weak(pos, pos.wrapping_add(1),
+                                                      Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => {
+                        let item = unsafe { (*slot.value.get()).assume_init_read() };
+                        O::store(&slot.stamp, pos.wrapping_add(self.capacity()));
+                        return Some(item);
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if diff < 0 {
+                return None;
+            } else {
+                pos = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<C, I, O> Drop for MpmcWheelBuf<C, I, O>
+    where C: Storage<Slot<I>>,
+          O: OrderingPolicy
+{
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+    use std::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn push_pop() {
+        let queue = MpmcWheelBuf::new([Slot::new(), Slot::new()]);
+
+        assert!(queue.pop().is_none());
+        assert_eq!(queue.push(1), Ok(()));
+        assert_eq!(queue.push(2), Ok(()));
+        assert_eq!(queue.push(3), Err(PushError(3)));
+        assert_eq!(queue.len(), 2);
+
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.push(3), Ok(()));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), Some(3));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn reused_slots() {
+        let mut slots = [Slot::new(), Slot::new(), Slot::new(), Slot::new()];
+        {
+            let queue = MpmcWheelBuf::new(&mut slots[1..3]);
+            assert_eq!(queue.push(1), Ok(()));
+        }
+
+        let queue = MpmcWheelBuf::new(&mut slots[..]);
+        assert!(queue.pop().is_none());
+        assert_eq!(queue.push(2), Ok(()));
+        assert_eq!(queue.pop(), Some(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn non_power_of_two() {
+        let _ = MpmcWheelBuf::<_, u8>::new([Slot::new(), Slot::new(), Slot::new()]);
+    }
+
+    #[test]
+    fn threads() {
+        let mut slots: Vec<Slot<usize>> = (0..8).map(|_| Slot::new()).collect();
+        let queue = MpmcWheelBuf::new(&mut slots[..]);
+        let sum = AtomicUsize::new(0);
+
+        thread::scope(|s| {
+            for t in 0..3 {
+                let queue = &queue;
+                s.spawn(move || {
+                    for i in 0..500 {
+                        while queue.push(t * 500 + i).is_err() {
+                            thread::yield_now();
+                        }
+                    }
+                });
+            }
+            for _ in 0..3 {
+                let (queue, sum) = (&queue, &sum);
+                s.spawn(move || {
+                    for _ in 0..500 {
+                        loop {
+                            match queue.pop() {
+                                Some(i) => {
+                                    sum.fetch_add(i, Ordering::Relaxed);
+                                    break;
+                                }
+                                None => thread::yield_now(),
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(sum.load(Ordering::Relaxed), (0..1500).sum());
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+//! Memory ordering policies for the concurrent queues.
+//!
+//! The queues synchronize the slot contents through their indices (or slot
+//! stamps). On a multi-core system, this requires acquire and release
+//! barriers. If producer and consumer only ever run on the same core, e.g.
+//! an interrupt handler and the main loop, it suffices to keep the compiler
+//! from reordering accesses, which saves the barrier instructions.
+
+use core::sync::atomic::{self, AtomicUsize, Ordering};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// How a concurrent queue publishes and observes positions.
+///
+/// This trait is sealed; use `MultiCore` or `SingleCore`.
+pub trait OrderingPolicy: sealed::Sealed {
+    /// Loads a position published by the other side.
+    #[doc(hidden)]
+    fn load(position: &AtomicUsize) -> usize;
+
+    /// Publishes a position to the other side.
+    #[doc(hidden)]
+    fn store(position: &AtomicUsize, value: usize);
+}
+
+/// Acquire loads and release stores, safe on any system. The default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MultiCore;
+
+/// Relaxed accesses with compiler fences.
+///
+/// Only correct if all users of the queue run on a single core, which the
+/// compiler cannot check; hence the constructors taking a policy are
+/// `unsafe`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SingleCore;
+
+impl sealed::Sealed for MultiCore {}
+impl sealed::Sealed for SingleCore {}
+
+impl OrderingPolicy for MultiCore {
+    #[inline]
+    fn load(position: &AtomicUsize) -> usize {
+        position.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    fn store(position: &AtomicUsize, value: usize) {
+        position.store(value, Ordering::Release)
+    }
+}
+
+impl OrderingPolicy for SingleCore {
+    #[inline]
+    fn load(position: &AtomicUsize) -> usize {
+        let value = position.load(Ordering::Relaxed);
+        atomic::compiler_fence(Ordering::Acquire);
+        value
+    }
+
+    #[inline]
+    fn store(position: &AtomicUsize, value: usize) {
+        atomic::compiler_fence(Ordering::Release);
+        position.store(value, Ordering::Relaxed)
+    }
+}
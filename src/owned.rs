@@ -0,0 +1,197 @@
+//! Heap-allocated backing stores, available with the `alloc` feature.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::{mask_for, WheelBuf};
+
+impl<I> WheelBuf<Vec<I>, I> {
+    /// Creates a new WheelBuf with a heap-allocated backing store.
+    ///
+    /// The store holds `capacity` items, initialized to `I::default()`.
+    pub fn with_capacity(capacity: usize) -> Self
+        where I: Default
+    {
+        let mut data = Vec::with_capacity(capacity);
+        data.resize_with(capacity, I::default);
+        WheelBuf::from_store(data)
+    }
+
+    /// Enlarges the capacity by `additional` items.
+    ///
+    /// See `grow_to()`.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize)
+        where I: Default
+    {
+        let capacity = self.capacity() + additional;
+        self.grow_to(capacity);
+    }
+
+    /// Enlarges the capacity to `capacity` items.
+    ///
+    /// The contents are moved to the start of the backing store, preserving
+    /// logical order, and new slots are filled with `I::default()`. Has no
+    /// effect if the buffer is already at least that large.
+    pub fn grow_to(&mut self, capacity: usize)
+        where I: Default
+    {
+        if capacity <= self.capacity() {
+            return;
+        }
+
+        if !self.is_empty() {
+            let start = self.read_start();
+            self.data.rotate_left(start);
+        }
+        self.data.resize_with(capacity, I::default);
+        self.mask = mask_for(capacity);
+        self.pos = self.len;
+    }
+
+    /// Reduces the capacity to the current number of items.
+    ///
+    /// See `shrink_to()`.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        let len = self.len;
+        self.shrink_to(len);
+    }
+
+    /// Reduces the capacity to `capacity` items.
+    ///
+    /// If the buffer holds more items, the oldest ones are discarded. The
+    /// remaining contents are moved to the start of the backing store and
+    /// unused heap memory is released. Has no effect if the buffer is
+    /// already at most that large.
+    pub fn shrink_to(&mut self, capacity: usize) {
+        if capacity >= self.capacity() {
+            return;
+        }
+
+        self.truncate_front(capacity);
+        if !self.is_empty() {
+            let start = self.read_start();
+            self.data.rotate_left(start);
+        }
+        self.data.truncate(capacity);
+        self.data.shrink_to_fit();
+        self.mask = mask_for(capacity);
+        self.pos = if self.len == capacity { 0 } else { self.len };
+    }
+}
+
+impl<I> WheelBuf<Box<[I]>, I> {
+    /// Creates a new WheelBuf backed by a boxed slice.
+    ///
+    /// The capacity is fixed to the length of `data`.
+    #[inline]
+    pub fn from_boxed_slice(data: Box<[I]>) -> Self {
+        WheelBuf::from_store(data)
+    }
+}
+
+impl<I> From<Box<[I]>> for WheelBuf<Box<[I]>, I> {
+    #[inline]
+    fn from(data: Box<[I]>) -> Self {
+        WheelBuf::from_boxed_slice(data)
+    }
+}
+
+impl<I> Default for WheelBuf<Vec<I>, I> {
+    /// Creates a buffer backed by an empty vector.
+    ///
+    /// It has no capacity and discards pushed items until it is enlarged
+    /// with `reserve()` or `grow_to()`.
+    #[inline]
+    fn default() -> Self {
+        WheelBuf::from_store(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::boxed::Box;
+    use std::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn with_capacity() {
+        let mut wheel = WheelBuf::with_capacity(3);
+        assert_eq!(wheel.capacity(), 3);
+        assert!(wheel.is_empty());
+
+        wheel.extend(1..5);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [2, 3, 4]);
+    }
+
+    #[test]
+    fn from_boxed_slice() {
+        let data: Box<[char]> = vec!['x'; 4].into_boxed_slice();
+        let mut wheel = WheelBuf::from_boxed_slice(data);
+
+        wheel.extend("Hello".chars());
+        assert_eq!(wheel.capacity(), 4);
+        assert_eq!(wheel.front(), Some(&'e'));
+
+        let wheel: WheelBuf<Box<[u8]>, u8> = WheelBuf::from(Box::from(&[0u8; 2][..]));
+        assert_eq!(wheel.capacity(), 2);
+    }
+
+    #[test]
+    fn grow() {
+        let mut wheel = WheelBuf::with_capacity(3);
+        wheel.extend(1..5);
+
+        wheel.reserve(2);
+        assert_eq!(wheel.capacity(), 5);
+        wheel.extend(5..7);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [2, 3, 4, 5, 6]);
+
+        wheel.pop_front();
+        wheel.grow_to(4);
+        assert_eq!(wheel.capacity(), 5);
+        wheel.grow_to(6);
+        wheel.extend(7..9);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn shrink() {
+        let mut wheel = WheelBuf::with_capacity(5);
+        wheel.extend(1..8);
+
+        wheel.shrink_to(3);
+        assert_eq!(wheel.capacity(), 3);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [5, 6, 7]);
+
+        wheel.push(8);
+        assert_eq!(wheel.front(), Some(&6));
+
+        wheel.pop_front();
+        wheel.shrink_to_fit();
+        assert_eq!(wheel.capacity(), 2);
+        let v: Vec<i32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [7, 8]);
+
+        wheel.shrink_to(0);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn default() {
+        let mut wheel: WheelBuf<Vec<u8>, u8> = WheelBuf::default();
+        assert_eq!(wheel.capacity(), 0);
+        wheel.push(1);
+        assert_eq!(wheel.push_evict(2), Some(2));
+        assert!(wheel.is_empty());
+
+        wheel.reserve(2);
+        wheel.push(3);
+        assert_eq!(wheel.front(), Some(&3));
+    }
+}
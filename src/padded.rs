@@ -0,0 +1,31 @@
+//! Alignment to keep independently written values on separate cache lines.
+
+use core::ops::Deref;
+
+/// Pads and aligns a value to the length of a cache line.
+///
+/// Only takes effect with the `cache-padded` feature enabled; otherwise the
+/// value is stored as is, which keeps small MCU targets from wasting RAM.
+#[cfg_attr(all(feature = "cache-padded", any(target_arch = "x86_64", target_arch = "aarch64")),
+           repr(align(128)))]
+#[cfg_attr(all(feature = "cache-padded", not(any(target_arch = "x86_64", target_arch = "aarch64"))),
+           repr(align(64)))]
+pub(crate) struct CachePadded<T> {
+    value: T,
+}
+
+impl<T> CachePadded<T> {
+    #[inline]
+    pub(crate) const fn new(value: T) -> CachePadded<T> {
+        CachePadded { value }
+    }
+}
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
@@ -0,0 +1,241 @@
+//! Push-only and pop-only handles to a single buffer.
+
+use core::cell::Cell;
+use core::mem;
+
+use super::{PushError, Storage, WheelBuf};
+
+/// The pushing half of a split WheelBuf.
+///
+/// Neither half can be sent to another thread; see the `spsc` module for
+/// that.
+pub struct WheelBufProducer<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+    buffer: &'a Cell<WheelBuf<C, I>>,
+}
+
+/// The popping half of a split WheelBuf.
+///
+/// As the producer may overwrite any item at any time, the consumer hands
+/// out items by value instead of by reference.
+pub struct WheelBufConsumer<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+    buffer: &'a Cell<WheelBuf<C, I>>,
+}
+
+impl<C, I> WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    /// Splits the buffer into a push-only and a pop-only handle.
+    ///
+    /// The handles share the buffer without a borrow flag, so the store
+    /// must not run user code while it is accessed, see `Storage`.
+    #[inline]
+    pub fn split<'a>(&'a mut self) -> (WheelBufProducer<'a, C, I>, WheelBufConsumer<'a, C, I>)
+        where C: Storage<I>
+    {
+        let buffer = Cell::from_mut(self);
+        (WheelBufProducer { buffer }, WheelBufConsumer { buffer })
+    }
+}
+
+/// Runs `f` on the buffer behind `cell`.
+///
+/// `f` must not call back into user code that could reach `cell`, such as
+/// item destructors or `Default` impls. Values to be dropped are returned
+/// from `f` instead, and new ones created before calling it. The store is
+/// one of the `Storage` types, whose slice accessors are not user code.
+#[inline]
+fn with<C, I, R, F>(cell: &Cell<WheelBuf<C, I>>, f: F) -> R
+    where C: AsMut<[I]> + AsRef<[I]>,
+          F: FnOnce(&mut WheelBuf<C, I>) -> R
+{
+    // The handles are neither `Send` nor `Sync`, and this is the only place
+    // the buffer is accessed through them.
+    f(unsafe { &mut *cell.as_ptr() })
+}
+
+impl<'a, C, I> WheelBufProducer<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    /// Add item to wheel buffer, following the buffer's overflow policy.
+    #[inline]
+    pub fn push(&mut self, item: I) {
+        // Drop the previous slot contents only after the buffer is released.
+        let _ = with(self.buffer, |b| b.replace_next(item));
+    }
+
+    /// Add item to wheel buffer, returning the item it displaced.
+    #[inline]
+    pub fn push_evict(&mut self, item: I) -> Option<I> {
+        let (full, replaced) = with(self.buffer, |b| (b.is_full(), b.replace_next(item)));
+        match replaced {
+            Ok(old) if full => Some(old),
+            Ok(_) => None,
+            Err(item) => Some(item),
+        }
+    }
+
+    /// Add item to wheel buffer, unless it is full.
+    #[inline]
+    pub fn try_push(&mut self, item: I) -> Result<(), PushError<I>> {
+        let _stale = with(self.buffer, |b| {
+            if b.is_full() {
+                Err(PushError(item))
+            } else {
+                Ok(b.replace_next(item))
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Number of items in buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        with(self.buffer, |b| b.len())
+    }
+
+    /// Returns `true` if the buffer holds no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the buffer is filled to capacity.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        with(self.buffer, |b| b.is_full())
+    }
+
+    /// Capacity of wheel buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        with(self.buffer, |b| b.capacity())
+    }
+}
+
+impl<'a, C, I> WheelBufConsumer<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    /// Removes the oldest item from the buffer.
+    ///
+    /// The vacated slot is reset to `I::default()`.
+    #[inline]
+    pub fn pop(&mut self) -> Option<I>
+        where I: Default
+    {
+        // Create the replacement, and drop it if unused, outside of the
+        // buffer access.
+        let default = I::default();
+        with(self.buffer, |b| {
+            if b.is_empty() {
+                return Err(default);
+            }
+
+            let idx = b.read_start();
+            b.len -= 1;
+            Ok(mem::replace(&mut b.data.as_mut()[idx], default))
+        }).ok()
+    }
+
+    /// Returns a copy of the oldest item, leaving it in the buffer.
+    #[inline]
+    pub fn peek(&self) -> Option<I>
+        where I: Copy
+    {
+        with(self.buffer, |b| b.front().copied())
+    }
+
+    /// Removes all items from the buffer.
+    #[inline]
+    pub fn clear(&mut self) {
+        with(self.buffer, |b| b.clear())
+    }
+
+    /// Number of items available to pop.
+    #[inline]
+    pub fn len(&self) -> usize {
+        with(self.buffer, |b| b.len())
+    }
+
+    /// Returns `true` if there is nothing to pop.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a, C, I> Iterator for WheelBufConsumer<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Default
+{
+    type Item = I;
+
+    #[inline]
+    fn next(&mut self) -> Option<I> {
+        self.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn split() {
+        let mut wheel = WheelBuf::new([0; 3]);
+        {
+            let (mut producer, mut consumer) = wheel.split();
+
+            producer.push(1);
+            producer.push(2);
+            assert_eq!(consumer.peek(), Some(1));
+            assert_eq!(consumer.pop(), Some(1));
+
+            producer.push(3);
+            producer.push(4);
+            assert!(producer.is_full());
+            assert_eq!(producer.try_push(5), Err(PushError(5)));
+            assert_eq!(producer.push_evict(5), Some(2));
+
+            let v: Vec<i32> = consumer.by_ref().collect();
+            assert_eq!(v, [3, 4, 5]);
+            assert!(producer.is_empty());
+        }
+        assert_eq!(wheel.total(), 5);
+    }
+
+    #[test]
+    fn drops() {
+        use std::rc::Rc;
+
+        let item = Rc::new(());
+        let mut wheel = WheelBuf::new([None, None]);
+        {
+            let (mut producer, mut consumer) = wheel.split();
+            producer.push(Some(item.clone()));
+            producer.push(Some(item.clone()));
+            consumer.clear();
+            assert_eq!(Rc::strong_count(&item), 3);
+
+            // The stale slot contents are dropped on the way out.
+            assert_eq!(producer.try_push(None), Ok(()));
+            assert_eq!(Rc::strong_count(&item), 2);
+            assert_eq!(producer.push_evict(None), None);
+            assert_eq!(Rc::strong_count(&item), 1);
+
+            producer.push(Some(item.clone()));
+            assert!(consumer.pop().is_some());
+            assert!(consumer.pop().unwrap().is_some());
+            assert!(consumer.pop().is_none());
+        }
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+}
@@ -0,0 +1,528 @@
+//! Lock-free single-producer single-consumer queue.
+//!
+//! An `AtomicWheelBuf` is split into a `Producer` and a `Consumer` half,
+//! which can live in different execution contexts, e.g. an interrupt handler
+//! and the main loop, without a critical section. Unlike `WheelBuf`, a full
+//! queue rejects new items instead of overwriting: the producer cannot
+//! reclaim a slot the consumer might be reading.
+//!
+//! With the `async` feature enabled, either half can register a task to be
+//! woken once the other half pushed an item or freed a slot.
+
+use core::cell::UnsafeCell;
+#[cfg(feature = "async")]
+use core::future::{self, Future};
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "async")]
+use core::task::{Context, Poll};
+
+use super::{PushError, Storage};
+use super::ordering::{MultiCore, OrderingPolicy};
+use super::padded::CachePadded;
+#[cfg(feature = "async")]
+use super::waker::AtomicWaker;
+
+/// A ring buffer with atomic indices, usable by one producer and one
+/// consumer concurrently.
+///
+/// Like `UninitWheelBuf`, the backing store holds `MaybeUninit<I>` slots,
+/// and must always present the same ones, see `Storage`.
+///
+/// Both indices run from `0` to `2 * capacity`, so that a full buffer can be
+/// told apart from an empty one without wasting a slot, and without relying
+/// on integer wraparound (which breaks for capacities that are not powers of
+/// two).
+pub struct AtomicWheelBuf<C, I, O = MultiCore>
+    where C: Storage<MaybeUninit<I>>,
+          O: OrderingPolicy
+{
+    /// Backend store
+    data: UnsafeCell<C>,
+
+    /// Write index, owned by the producer
+    head: CachePadded<AtomicUsize>,
+
+    /// Read index, owned by the consumer
+    tail: CachePadded<AtomicUsize>,
+
+    /// Consumer task waiting for an item
+    #[cfg(feature = "async")]
+    readable: AtomicWaker,
+
+    /// Producer task waiting for a free slot
+    #[cfg(feature = "async")]
+    writable: AtomicWaker,
+
+    _pd: PhantomData<(I, O)>,
+}
+
+unsafe impl<C, I, O> Sync for AtomicWheelBuf<C, I, O>
+    where C: Storage<MaybeUninit<I>> + Send,
+          I: Send,
+          O: OrderingPolicy
+{
+}
+
+/// The pushing half of a split AtomicWheelBuf.
+pub struct Producer<'a, C, I, O = MultiCore>
+    where C: Storage<MaybeUninit<I>>,
+          I: 'a,
+          C: 'a,
+          O: OrderingPolicy + 'a
+{
+    buffer: &'a AtomicWheelBuf<C, I, O>,
+    slots: *mut MaybeUninit<I>,
+    cap: usize,
+}
+
+/// The popping half of a split AtomicWheelBuf.
+pub struct Consumer<'a, C, I, O = MultiCore>
+    where C: Storage<MaybeUninit<I>>,
+          I: 'a,
+          C: 'a,
+          O: OrderingPolicy + 'a
+{
+    buffer: &'a AtomicWheelBuf<C, I, O>,
+    slots: *mut MaybeUninit<I>,
+    cap: usize,
+}
+
+unsafe impl<'a, C, I, O> Send for Producer<'a, C, I, O>
+    where C: Storage<MaybeUninit<I>> + Send,
+          I: Send,
+          O: OrderingPolicy
+{
+}
+
+unsafe impl<'a, C, I, O> Send for Consumer<'a, C, I, O>
+    where C: Storage<MaybeUninit<I>> + Send,
+          I: Send,
+          O: OrderingPolicy
+{
+}
+
+impl<C, I> AtomicWheelBuf<C, I>
+    where C: Storage<MaybeUninit<I>>
+{
+    /// Creates a new, empty AtomicWheelBuf.
+    ///
+    /// The contents of `data` are ignored and never dropped. The `len()` of
+    /// data determines the size of the buffer.
+    #[inline]
+    pub const fn new(data: C) -> AtomicWheelBuf<C, I> {
+        // `MultiCore` is correct on any system.
+        unsafe { AtomicWheelBuf::with_ordering(data) }
+    }
+}
+
+impl<C, I, O> AtomicWheelBuf<C, I, O>
+    where C: Storage<MaybeUninit<I>>,
+          O: OrderingPolicy
+{
+    /// Creates a new, empty AtomicWheelBuf using the ordering policy `O`.
+    ///
+    /// # Safety
+    ///
+    /// With `SingleCore`, both halves must only ever be used from the same
+    /// core. Running them on different cores races on the slot contents.
+    #[inline]
+    pub const unsafe fn with_ordering(data: C) -> AtomicWheelBuf<C, I, O> {
+        AtomicWheelBuf {
+            data: UnsafeCell::new(data),
+            head: CachePadded::new(AtomicUsize::new(0)),
+            tail: CachePadded::new(AtomicUsize::new(0)),
+            #[cfg(feature = "async")]
+            readable: AtomicWaker::new(),
+            #[cfg(feature = "async")]
+            writable: AtomicWaker::new(),
+            _pd: PhantomData,
+        }
+    }
+
+    /// Splits the buffer into its producer and consumer halves.
+    #[inline]
+    pub fn split<'a>(&'a mut self) -> (Producer<'a, C, I, O>, Consumer<'a, C, I, O>) {
+        let slots = self.data.get_mut().slots_mut();
+        let cap = slots.len();
+        let slots = slots.as_mut_ptr();
+        let buffer = &*self;
+
+        (Producer { buffer, slots, cap }, Consumer { buffer, slots, cap })
+    }
+}
+
+impl<C, I, O> Drop for AtomicWheelBuf<C, I, O>
+    where C: Storage<MaybeUninit<I>>,
+          O: OrderingPolicy
+{
+    fn drop(&mut self) {
+        let (_, mut consumer) = self.split();
+        while consumer.pop().is_some() {}
+    }
+}
+
+impl<'a, C, I, O> Producer<'a, C, I, O>
+    where C: Storage<MaybeUninit<I>>,
+          O: OrderingPolicy
+{
+    /// Add item to the buffer, unless it is full.
+    #[inline]
+    pub fn push(&mut self, item: I) -> Result<(), PushError<I>> {
+        let head = self.buffer.head.load(Ordering::Relaxed);
+        let tail = O::load(&self.buffer.tail);
+        if distance(tail, head, self.cap) == self.cap {
+            return Err(PushError(item));
+        }
+
+        // The slot lies outside the consumer's range until `head` is
+        // published below.
+        unsafe {
+            (*self.slots.add(slot(head, self.cap))).write(item);
+        }
+        O::store(&self.buffer.head, advance(head, self.cap));
+
+        #[cfg(feature = "async")]
+        self.buffer.readable.wake();
+        Ok(())
+    }
+
+    /// Checks for a free slot, registering the current task to be woken
+    /// by the consumer if there is none.
+    ///
+    /// Like `Sink::poll_ready`: once this returns `Ready`, the next `push()`
+    /// succeeds.
+    #[cfg(feature = "async")]
+    pub fn poll_ready(&mut self, cx: &mut Context) -> Poll<()> {
+        if !self.is_full() {
+            return Poll::Ready(());
+        }
+
+        self.buffer.writable.register(cx.waker());
+
+        // Check again, the consumer may have freed a slot before the waker
+        // was in place.
+        if self.is_full() { Poll::Pending } else { Poll::Ready(()) }
+    }
+
+    /// Waits until there is a free slot.
+    #[cfg(feature = "async")]
+    #[inline]
+    pub fn wait_for_space<'b>(&'b mut self) -> impl Future<Output = ()> + use<'a, 'b, C, I, O> {
+        future::poll_fn(move |cx| self.poll_ready(cx))
+    }
+
+    /// Number of items in buffer.
+    ///
+    /// The consumer may pop concurrently, so this is an upper bound.
+    #[inline]
+    pub fn len(&self) -> usize {
+        let tail = O::load(&self.buffer.tail);
+        let head = self.buffer.head.load(Ordering::Relaxed);
+        distance(tail, head, self.cap)
+    }
+
+    /// Returns `true` if the buffer holds no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the buffer is filled to capacity.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() == self.cap
+    }
+
+    /// Capacity of the buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+}
+
+impl<'a, C, I, O> Consumer<'a, C, I, O>
+    where C: Storage<MaybeUninit<I>>,
+          O: OrderingPolicy
+{
+    /// Removes the oldest item from the buffer.
+    #[inline]
+    pub fn pop(&mut self) -> Option<I> {
+        let tail = self.buffer.tail.load(Ordering::Relaxed);
+        let head = O::load(&self.buffer.head);
+        if head == tail {
+            return None;
+        }
+
+        // The producer published the slot and will not touch it again until
+        // `tail` moves past it.
+        let item = unsafe { ptr::read(self.slots.add(slot(tail, self.cap))).assume_init() };
+        O::store(&self.buffer.tail, advance(tail, self.cap));
+
+        #[cfg(feature = "async")]
+        self.buffer.writable.wake();
+        Some(item)
+    }
+
+    /// Removes the oldest item, registering the current task to be woken by
+    /// the producer if there is none.
+    ///
+    /// This is `Stream::poll_next` for a stream that never ends.
+    #[cfg(feature = "async")]
+    pub fn poll_pop(&mut self, cx: &mut Context) -> Poll<I> {
+        if let Some(item) = self.pop() {
+            return Poll::Ready(item);
+        }
+
+        self.buffer.readable.register(cx.waker());
+
+        // Check again, the producer may have pushed before the waker was in
+        // place.
+        match self.pop() {
+            Some(item) => Poll::Ready(item),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Checks for an item, registering the current task to be woken by the
+    /// producer if there is none.
+    #[cfg(feature = "async")]
+    pub fn poll_data(&mut self, cx: &mut Context) -> Poll<()> {
+        if !self.is_empty() {
+            return Poll::Ready(());
+        }
+
+        self.buffer.readable.register(cx.waker());
+        if self.is_empty() { Poll::Pending } else { Poll::Ready(()) }
+    }
+
+    /// Waits until there is an item to pop.
+    #[cfg(feature = "async")]
+    #[inline]
+    pub fn wait_for_data<'b>(&'b mut self) -> impl Future<Output = ()> + use<'a, 'b, C, I, O> {
+        future::poll_fn(move |cx| self.poll_data(cx))
+    }
+
+    /// Returns a reference to the oldest item, if any.
+    #[inline]
+    pub fn peek(&self) -> Option<&I> {
+        let tail = self.buffer.tail.load(Ordering::Relaxed);
+        let head = O::load(&self.buffer.head);
+        if head == tail {
+            return None;
+        }
+
+        Some(unsafe { (*self.slots.add(slot(tail, self.cap))).assume_init_ref() })
+    }
+
+    /// Number of items available to pop.
+    ///
+    /// The producer may push concurrently, so this is a lower bound.
+    #[inline]
+    pub fn len(&self) -> usize {
+        let tail = self.buffer.tail.load(Ordering::Relaxed);
+        let head = O::load(&self.buffer.head);
+        distance(tail, head, self.cap)
+    }
+
+    /// Returns `true` if there is nothing to pop.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Number of items between the indices `tail` and `head`.
+#[inline]
+fn distance(tail: usize, head: usize, cap: usize) -> usize {
+    if head >= tail {
+        head - tail
+    } else {
+        head + 2 * cap - tail
+    }
+}
+
+/// Index following `idx`, wrapping at `2 * cap`.
+#[inline]
+fn advance(idx: usize, cap: usize) -> usize {
+    if idx + 1 == 2 * cap { 0 } else { idx + 1 }
+}
+
+/// Slot addressed by the index `idx`.
+#[inline]
+fn slot(idx: usize, cap: usize) -> usize {
+    if idx >= cap { idx - cap } else { idx }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    #[cfg(feature = "async")]
+    use std::sync::Arc;
+    #[cfg(feature = "async")]
+    use std::sync::atomic::AtomicUsize;
+    #[cfg(feature = "async")]
+    use std::task::{Wake, Waker};
+    use std::thread;
+    use super::*;
+    use super::super::ordering::SingleCore;
+
+    #[test]
+    fn push_pop() {
+        let mut buf: [MaybeUninit<u32>; 2] = [MaybeUninit::uninit(); 2];
+        let mut queue = AtomicWheelBuf::new(&mut buf);
+        let (mut producer, mut consumer) = queue.split();
+
+        assert!(consumer.pop().is_none());
+        assert_eq!(producer.push(1), Ok(()));
+        assert_eq!(producer.push(2), Ok(()));
+        assert_eq!(producer.push(3), Err(PushError(3)));
+        assert!(producer.is_full());
+
+        assert_eq!(consumer.peek(), Some(&1));
+        assert_eq!(consumer.pop(), Some(1));
+        assert_eq!(producer.push(3), Ok(()));
+        assert_eq!(consumer.pop(), Some(2));
+        assert_eq!(consumer.pop(), Some(3));
+        assert!(consumer.is_empty());
+        assert!(producer.is_empty());
+    }
+
+    #[test]
+    fn resplit() {
+        let mut queue = AtomicWheelBuf::new([MaybeUninit::<u32>::uninit(); 2]);
+        {
+            let (mut producer, _) = queue.split();
+            assert_eq!(producer.push(1), Ok(()));
+        }
+        let (_, mut consumer) = queue.split();
+        assert_eq!(consumer.pop(), Some(1));
+        assert!(consumer.pop().is_none());
+    }
+
+    #[test]
+    fn single_core() {
+        let mut buf: [MaybeUninit<u32>; 2] = [MaybeUninit::uninit(); 2];
+        let mut queue = unsafe { AtomicWheelBuf::<_, _, SingleCore>::with_ordering(&mut buf) };
+        let (mut producer, mut consumer) = queue.split();
+
+        producer.push(1).unwrap();
+        producer.push(2).unwrap();
+        assert!(producer.push(3).is_err());
+        assert_eq!(consumer.pop(), Some(1));
+        assert_eq!(consumer.pop(), Some(2));
+        assert!(consumer.pop().is_none());
+    }
+
+    #[test]
+    fn odd_capacity() {
+        let mut buf: [MaybeUninit<u32>; 3] = [MaybeUninit::uninit(); 3];
+        let mut queue = AtomicWheelBuf::new(&mut buf);
+        let (mut producer, mut consumer) = queue.split();
+
+        for i in 0..20 {
+            producer.push(i).unwrap();
+            producer.push(i + 100).unwrap();
+            assert_eq!(consumer.len(), 2);
+            assert_eq!(consumer.pop(), Some(i));
+            assert_eq!(consumer.pop(), Some(i + 100));
+        }
+    }
+
+    #[test]
+    fn drops_remaining() {
+        let item = Rc::new(());
+        {
+            let mut queue = AtomicWheelBuf::new([MaybeUninit::uninit(), MaybeUninit::uninit()]);
+            let (mut producer, _) = queue.split();
+            producer.push(item.clone()).unwrap();
+            assert_eq!(Rc::strong_count(&item), 2);
+        }
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn threads() {
+        let mut buf: [MaybeUninit<usize>; 4] = [MaybeUninit::uninit(); 4];
+        let mut queue = AtomicWheelBuf::new(&mut buf);
+        let (mut producer, mut consumer) = queue.split();
+
+        thread::scope(|s| {
+            s.spawn(move || {
+                for i in 0..1000 {
+                    while producer.push(i).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            });
+
+            let mut expected = 0;
+            while expected < 1000 {
+                match consumer.pop() {
+                    Some(i) => {
+                        assert_eq!(i, expected);
+                        expected += 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+        });
+    }
+
+    #[cfg(feature = "async")]
+    struct CountingWaker(AtomicUsize);
+
+    #[cfg(feature = "async")]
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn poll() {
+        let wakes = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(wakes.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut buf: [MaybeUninit<u32>; 1] = [MaybeUninit::uninit(); 1];
+        let mut queue = AtomicWheelBuf::new(&mut buf);
+        let (mut producer, mut consumer) = queue.split();
+
+        assert_eq!(consumer.poll_pop(&mut cx), Poll::Pending);
+        assert_eq!(producer.poll_ready(&mut cx), Poll::Ready(()));
+        producer.push(1).unwrap();
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+
+        assert_eq!(producer.poll_ready(&mut cx), Poll::Pending);
+        assert_eq!(consumer.poll_pop(&mut cx), Poll::Ready(1));
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 2);
+        assert_eq!(producer.poll_ready(&mut cx), Poll::Ready(()));
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn wait() {
+        let waker = Waker::noop();
+        let mut cx = Context::from_waker(waker);
+
+        let mut buf: [MaybeUninit<u32>; 1] = [MaybeUninit::uninit(); 1];
+        let mut queue = AtomicWheelBuf::new(&mut buf);
+        let (mut producer, mut consumer) = queue.split();
+
+        {
+            let mut data = core::pin::pin!(consumer.wait_for_data());
+            assert_eq!(data.as_mut().poll(&mut cx), Poll::Pending);
+            producer.push(1).unwrap();
+            assert_eq!(data.as_mut().poll(&mut cx), Poll::Ready(()));
+        }
+
+        let mut space = core::pin::pin!(producer.wait_for_space());
+        assert_eq!(space.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(consumer.pop(), Some(1));
+        assert_eq!(space.as_mut().poll(&mut cx), Poll::Ready(()));
+    }
+}
@@ -0,0 +1,1123 @@
+//! Window statistics maintained incrementally as items are pushed.
+//!
+//! Each type wraps a WheelBuf and updates its statistic whenever an item
+//! enters or leaves the window, so reading it is O(1). The wrapped buffer is
+//! only handed out immutably, since changing it behind the wrapper's back
+//! would invalidate the statistic.
+
+use core::cmp;
+use core::iter::FusedIterator;
+use core::ops::{Add, Mul, Sub};
+
+use super::{WheelBuf, WheelBufIter};
+
+/// A WheelBuf that keeps a running sum of its items.
+///
+/// `I::default()` is taken as zero. With floating point items, rounding
+/// errors accumulate over many pushes; `recompute()` starts afresh.
+#[derive(Clone, Debug)]
+pub struct RollingSum<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    buffer: WheelBuf<C, I>,
+    sum: I,
+}
+
+impl<C, I> RollingSum<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Add<Output = I> + Sub<Output = I> + Copy + Default
+{
+    /// Creates a new, empty RollingSum.
+    ///
+    /// The `len()` of data determines the size of the window.
+    #[inline]
+    pub fn new(data: C) -> RollingSum<C, I> {
+        RollingSum::from_buffer(WheelBuf::from_store(data))
+    }
+
+    /// Wraps an existing buffer, summing up its items.
+    #[inline]
+    pub fn from_buffer(buffer: WheelBuf<C, I>) -> RollingSum<C, I> {
+        let mut rolling = RollingSum {
+            buffer,
+            sum: I::default(),
+        };
+        rolling.recompute();
+        rolling
+    }
+
+    /// Add item to the window, following the buffer's overflow policy.
+    #[inline]
+    pub fn push(&mut self, item: I) {
+        let total = self.buffer.total();
+        match self.buffer.push_evict(item) {
+            None => self.sum = self.sum + item,
+            Some(old) if self.buffer.total() != total => self.sum = self.sum - old + item,
+            Some(_) => {}
+        }
+    }
+
+    /// Removes the oldest item from the window.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<I> {
+        let item = self.buffer.pop_front()?;
+        self.sum = self.sum - item;
+        Some(item)
+    }
+
+    /// Removes all items from the window.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.sum = I::default();
+    }
+
+    /// Sum of all items in the window.
+    #[inline]
+    pub fn sum(&self) -> I {
+        self.sum
+    }
+
+    /// Arithmetic mean of the items in the window, or `None` if it is empty.
+    #[inline]
+    pub fn mean(&self) -> Option<f64>
+        where I: Into<f64>
+    {
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        Some(self.sum.into() / self.buffer.len() as f64)
+    }
+
+    /// Recalculates the sum from the items in the window.
+    #[inline]
+    pub fn recompute(&mut self) {
+        self.sum = self.buffer.iter().fold(I::default(), |acc, &item| acc + item);
+    }
+
+    /// Returns the wrapped buffer.
+    #[inline]
+    pub fn buffer(&self) -> &WheelBuf<C, I> {
+        &self.buffer
+    }
+
+    /// Unwraps the buffer.
+    #[inline]
+    pub fn into_inner(self) -> WheelBuf<C, I> {
+        self.buffer
+    }
+}
+
+/// A WheelBuf that tracks the minimum and maximum of its items.
+///
+/// Keeps two monotonic wedges of candidate items, stored as sequence
+/// numbers in `S`, which must hold at least as many slots as the window.
+/// Pushing is amortized O(1), reading the extremes is O(1).
+#[derive(Clone, Debug)]
+pub struct RollingMinMax<C, I, S>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          S: AsMut<[usize]> + AsRef<[usize]>
+{
+    buffer: WheelBuf<C, I>,
+
+    /// Candidates for the minimum, with increasing values
+    min: WheelBuf<S, usize>,
+
+    /// Candidates for the maximum, with decreasing values
+    max: WheelBuf<S, usize>,
+}
+
+impl<C, I, S> RollingMinMax<C, I, S>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: PartialOrd,
+          S: AsMut<[usize]> + AsRef<[usize]>
+{
+    /// Creates a new, empty RollingMinMax.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min_store` or `max_store` is shorter than `data`.
+    pub fn new(data: C, min_store: S, max_store: S) -> RollingMinMax<C, I, S> {
+        let buffer = WheelBuf::from_store(data);
+        let min = WheelBuf::new(min_store);
+        let max = WheelBuf::new(max_store);
+        assert!(min.capacity() >= buffer.capacity() && max.capacity() >= buffer.capacity(),
+                "wedge stores must hold at least {} items",
+                buffer.capacity());
+
+        RollingMinMax { buffer, min, max }
+    }
+
+    /// Add item to the window, following the buffer's overflow policy.
+    pub fn push(&mut self, item: I) {
+        let last = self.buffer.last_seq();
+        self.buffer.push(item);
+        let seq = match self.buffer.last_seq() {
+            Some(seq) if Some(seq) != last => seq,
+            _ => return,
+        };
+
+        self.expire();
+
+        let buffer = &self.buffer;
+        let item = &buffer[buffer.len() - 1];
+        let value = |s: usize| buffer.get_seq(s).unwrap();
+
+        while self.min.back().is_some_and(|&s| value(s) >= item) {
+            self.min.pop_back();
+        }
+        while self.max.back().is_some_and(|&s| value(s) <= item) {
+            self.max.pop_back();
+        }
+        self.min.push(seq);
+        self.max.push(seq);
+    }
+
+    /// Removes the oldest item from the window.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<I>
+        where I: Default
+    {
+        let item = self.buffer.pop_front()?;
+        self.expire();
+        Some(item)
+    }
+
+    /// Removes all items from the window.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.min.clear();
+        self.max.clear();
+    }
+
+    /// Smallest item in the window.
+    #[inline]
+    pub fn min(&self) -> Option<&I> {
+        self.buffer.get_seq(*self.min.front()?)
+    }
+
+    /// Largest item in the window.
+    #[inline]
+    pub fn max(&self) -> Option<&I> {
+        self.buffer.get_seq(*self.max.front()?)
+    }
+
+    /// Returns the wrapped buffer.
+    #[inline]
+    pub fn buffer(&self) -> &WheelBuf<C, I> {
+        &self.buffer
+    }
+
+    /// Unwraps the buffer.
+    #[inline]
+    pub fn into_inner(self) -> WheelBuf<C, I> {
+        self.buffer
+    }
+
+    /// Drops candidates that have left the window.
+    #[inline]
+    fn expire(&mut self) {
+        let start = self.buffer.seq_start();
+        while self.min.front().is_some_and(|&s| s < start) {
+            self.min.pop_front();
+        }
+        while self.max.front().is_some_and(|&s| s < start) {
+            self.max.pop_front();
+        }
+    }
+}
+
+/// A WheelBuf that keeps the running mean and variance of its items.
+///
+/// Uses Welford's algorithm, extended to remove items leaving the window,
+/// which is numerically stable unlike tracking the sum of squares.
+#[derive(Clone, Debug)]
+pub struct RollingVariance<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    buffer: WheelBuf<C, I>,
+    mean: f64,
+
+    /// Sum of squared differences from the mean
+    m2: f64,
+}
+
+impl<C, I> RollingVariance<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Into<f64> + Copy
+{
+    /// Creates a new, empty RollingVariance.
+    #[inline]
+    pub fn new(data: C) -> RollingVariance<C, I> {
+        RollingVariance::from_buffer(WheelBuf::from_store(data))
+    }
+
+    /// Wraps an existing buffer, accumulating its items.
+    pub fn from_buffer(buffer: WheelBuf<C, I>) -> RollingVariance<C, I> {
+        let mut rolling = RollingVariance {
+            buffer,
+            mean: 0.0,
+            m2: 0.0,
+        };
+
+        // Accumulate the items as if they were pushed one by one.
+        let mut n = 0;
+        for &item in rolling.buffer.iter() {
+            n += 1;
+            let x = item.into();
+            let delta = x - rolling.mean;
+            rolling.mean += delta / n as f64;
+            rolling.m2 += delta * (x - rolling.mean);
+        }
+        rolling
+    }
+
+    /// Add item to the window, following the buffer's overflow policy.
+    pub fn push(&mut self, item: I) {
+        let total = self.buffer.total();
+        let evicted = self.buffer.push_evict(item);
+        if self.buffer.total() == total {
+            return;
+        }
+
+        let n = self.buffer.len();
+        if let Some(old) = evicted {
+            self.remove(old.into(), n - 1);
+        }
+
+        let x = item.into();
+        let delta = x - self.mean;
+        self.mean += delta / n as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Removes the oldest item from the window.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<I>
+        where I: Default
+    {
+        let item = self.buffer.pop_front()?;
+        let n = self.buffer.len();
+        self.remove(item.into(), n);
+        Some(item)
+    }
+
+    /// Removes all items from the window.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.mean = 0.0;
+        self.m2 = 0.0;
+    }
+
+    /// Arithmetic mean of the items in the window, or `None` if it is empty.
+    #[inline]
+    pub fn mean(&self) -> Option<f64> {
+        if self.buffer.is_empty() { None } else { Some(self.mean) }
+    }
+
+    /// Population variance of the items in the window, or `None` if it is
+    /// empty.
+    #[inline]
+    pub fn variance(&self) -> Option<f64> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        // Removals can leave a tiny negative rounding error.
+        Some((self.m2 / self.buffer.len() as f64).max(0.0))
+    }
+
+    /// Sample variance of the items in the window, or `None` if it holds
+    /// fewer than two items.
+    #[inline]
+    pub fn sample_variance(&self) -> Option<f64> {
+        if self.buffer.len() < 2 {
+            return None;
+        }
+
+        Some((self.m2 / (self.buffer.len() - 1) as f64).max(0.0))
+    }
+
+    /// Population standard deviation of the items in the window.
+    #[inline]
+    pub fn stddev(&self) -> Option<f64> {
+        self.variance().map(sqrt)
+    }
+
+    /// Returns the wrapped buffer.
+    #[inline]
+    pub fn buffer(&self) -> &WheelBuf<C, I> {
+        &self.buffer
+    }
+
+    /// Unwraps the buffer.
+    #[inline]
+    pub fn into_inner(self) -> WheelBuf<C, I> {
+        self.buffer
+    }
+
+    /// Takes `x` out of the accumulated mean and variance, leaving `n`
+    /// items.
+    #[inline]
+    fn remove(&mut self, x: f64, n: usize) {
+        if n == 0 {
+            self.mean = 0.0;
+            self.m2 = 0.0;
+            return;
+        }
+
+        let delta = x - self.mean;
+        self.mean -= delta / n as f64;
+        self.m2 -= delta * (x - self.mean);
+    }
+}
+
+impl<C, I> WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Into<f64> + Copy
+{
+    /// Exponential moving average over the window, oldest first.
+    ///
+    /// Starts with the oldest item and blends in each following item with
+    /// weight `alpha`, which should lie in `0.0..=1.0`. Returns `None` if the
+    /// buffer is empty.
+    pub fn ema(&self, alpha: f64) -> Option<f64> {
+        let mut iter = self.iter();
+        let first = (*iter.next()?).into();
+        Some(iter.fold(first, |avg, &x| avg + alpha * (x.into() - avg)))
+    }
+
+    /// Weighted mean of the newest items.
+    ///
+    /// `weights[0]` applies to the newest item, `weights[1]` to the one
+    /// before, and so on; only as many items as there are weights are used.
+    /// Returns `None` if no item is used or the used weights sum to zero.
+    pub fn weighted_mean(&self, weights: &[f64]) -> Option<f64> {
+        let (sum, norm) = self.iter()
+                              .rev()
+                              .zip(weights)
+                              .fold((0.0, 0.0), |(sum, norm), (&x, &w)| (sum + w * x.into(), norm + w));
+
+        if norm == 0.0 { None } else { Some(sum / norm) }
+    }
+}
+
+impl<C, I> WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Add<Output = I> + Mul<Output = I> + Copy + Default
+{
+    /// Dot product of the newest items with `coeffs`.
+    ///
+    /// `coeffs[0]` is multiplied with the newest item, `coeffs[1]` with the
+    /// one before, and so on, which evaluates an FIR filter with the buffer
+    /// as its delay line. Surplus coefficients or items are ignored;
+    /// `I::default()` is taken as zero.
+    pub fn dot(&self, coeffs: &[I]) -> I {
+        let n = cmp::min(self.len, coeffs.len());
+        let (first, second) = self.as_slices();
+
+        // Newest `n` items, split at the end of the backing store.
+        let (older, newer) = if second.len() >= n {
+            (&[][..], &second[second.len() - n..])
+        } else {
+            (&first[first.len() - (n - second.len())..], second)
+        };
+
+        let (near, far) = coeffs[..n].split_at(newer.len());
+        let acc = newer.iter().rev().zip(near).fold(I::default(), |acc, (&x, &c)| acc + x * c);
+        older.iter().rev().zip(far).fold(acc, |acc, (&x, &c)| acc + x * c)
+    }
+}
+
+impl<C, I> WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Clone + PartialOrd
+{
+    /// Median of the items in the window, using `scratch` as work space.
+    ///
+    /// For an even number of items, returns the lower of the two middle
+    /// items. Unordered items such as NaN compare equal to everything. Runs
+    /// in linear time on average. Returns `None` if the buffer is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scratch` is shorter than `len()`.
+    pub fn median_into<'a>(&self, scratch: &'a mut [I]) -> Option<&'a I> {
+        assert!(scratch.len() >= self.len,
+                "scratch space of {} items is too small for {} items",
+                scratch.len(),
+                self.len);
+
+        if self.is_empty() {
+            return None;
+        }
+
+        let n = self.peek_into(scratch);
+        let (_, median, _) = scratch[..n].select_nth_unstable_by((n - 1) / 2, |a, b| {
+            a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal)
+        });
+        Some(median)
+    }
+}
+
+/// Streaming estimator for a single quantile, using the P² algorithm.
+///
+/// Tracks five markers whose heights approximate the minimum, the `p/2`,
+/// `p` and `(1+p)/2` quantiles and the maximum, adjusting them with
+/// piecewise-parabolic interpolation. Needs constant space and no sorting,
+/// but cannot forget observations; see `RollingQuantile` for a windowed
+/// variant.
+#[derive(Clone, Debug)]
+pub struct P2Quantile {
+    p: f64,
+
+    /// Marker heights
+    q: [f64; 5],
+
+    /// Marker positions
+    n: [f64; 5],
+
+    /// Desired marker positions
+    desired: [f64; 5],
+
+    /// Number of observations
+    count: usize,
+}
+
+impl P2Quantile {
+    /// Creates an estimator for the `p` quantile, e.g. `0.95` for p95.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not within `0.0..=1.0`.
+    pub fn new(p: f64) -> P2Quantile {
+        assert!((0.0..=1.0).contains(&p), "quantile {} out of range", p);
+
+        let mut estimator = P2Quantile {
+            p,
+            q: [0.0; 5],
+            n: [0.0; 5],
+            desired: [0.0; 5],
+            count: 0,
+        };
+        estimator.reset();
+        estimator
+    }
+
+    /// Forgets all observations.
+    pub fn reset(&mut self) {
+        let p = self.p;
+        self.n = [1.0, 2.0, 3.0, 4.0, 5.0];
+        self.desired = [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0];
+        self.count = 0;
+    }
+
+    /// Number of observations since the last reset.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Adds an observation.
+    pub fn push(&mut self, x: f64) {
+        if self.count < 5 {
+            self.q[self.count] = x;
+            self.count += 1;
+            if self.count == 5 {
+                sort(&mut self.q);
+            }
+            return;
+        }
+        self.count += 1;
+
+        let q = &mut self.q;
+        let k = if x < q[0] {
+            q[0] = x;
+            0
+        } else if x >= q[4] {
+            q[4] = x;
+            3
+        } else {
+            (0..4).find(|&i| x < q[i + 1]).unwrap()
+        };
+
+        for i in k + 1..5 {
+            self.n[i] += 1.0;
+        }
+        let p = self.p;
+        let increments = [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0];
+        for (desired, inc) in self.desired.iter_mut().zip(&increments) {
+            *desired += inc;
+        }
+
+        let n = &mut self.n;
+        for i in 1..4 {
+            let d = self.desired[i] - n[i];
+            if (d >= 1.0 && n[i + 1] - n[i] > 1.0) || (d <= -1.0 && n[i - 1] - n[i] < -1.0) {
+                let d = d.signum();
+                let parabolic = q[i] +
+                                d / (n[i + 1] - n[i - 1]) *
+                                ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i]) +
+                                 (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]));
+
+                q[i] = if q[i - 1] < parabolic && parabolic < q[i + 1] {
+                    parabolic
+                } else {
+                    let j = if d > 0.0 { i + 1 } else { i - 1 };
+                    q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+                };
+                n[i] += d;
+            }
+        }
+    }
+
+    /// Current estimate of the quantile, or `None` without observations.
+    ///
+    /// Exact for up to five observations.
+    pub fn estimate(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            count if count <= 5 => {
+                let mut q = self.q;
+                sort(&mut q[..count]);
+                let idx = (self.p * (count - 1) as f64 + 0.5) as usize;
+                Some(q[idx])
+            }
+            _ => Some(self.q[2]),
+        }
+    }
+}
+
+/// A WheelBuf with an approximate quantile of its recent items.
+///
+/// P² estimators cannot remove observations, so two of them take turns:
+/// each is restarted every `capacity()` pushes, half a window apart, and the
+/// older one provides the estimate. It therefore covers between the last
+/// half and the last full window of pushed items.
+#[derive(Clone, Debug)]
+pub struct RollingQuantile<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    buffer: WheelBuf<C, I>,
+    estimators: [P2Quantile; 2],
+}
+
+impl<C, I> RollingQuantile<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Into<f64> + Copy
+{
+    /// Creates a new, empty RollingQuantile for the `p` quantile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not within `0.0..=1.0`.
+    #[inline]
+    pub fn new(data: C, p: f64) -> RollingQuantile<C, I> {
+        RollingQuantile {
+            buffer: WheelBuf::from_store(data),
+            estimators: [P2Quantile::new(p), P2Quantile::new(p)],
+        }
+    }
+
+    /// Add item to the window, following the buffer's overflow policy.
+    pub fn push(&mut self, item: I) {
+        let total = self.buffer.total();
+        self.buffer.push(item);
+        if self.buffer.total() == total {
+            return;
+        }
+
+        for estimator in &mut self.estimators {
+            estimator.push(item.into());
+        }
+
+        let half = cmp::max(self.buffer.capacity() / 2, 1);
+        if self.buffer.total().is_multiple_of(half) {
+            let oldest = self.oldest();
+            self.estimators[oldest].reset();
+        }
+    }
+
+    /// Removes all items from the window.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        for estimator in &mut self.estimators {
+            estimator.reset();
+        }
+    }
+
+    /// Approximate quantile of the recent items, or `None` if there are
+    /// none.
+    #[inline]
+    pub fn quantile(&self) -> Option<f64> {
+        self.estimators[self.oldest()].estimate()
+    }
+
+    /// Returns the wrapped buffer.
+    #[inline]
+    pub fn buffer(&self) -> &WheelBuf<C, I> {
+        &self.buffer
+    }
+
+    /// Unwraps the buffer.
+    #[inline]
+    pub fn into_inner(self) -> WheelBuf<C, I> {
+        self.buffer
+    }
+
+    /// Index of the estimator with the most observations.
+    #[inline]
+    fn oldest(&self) -> usize {
+        if self.estimators[1].count() > self.estimators[0].count() { 1 } else { 0 }
+    }
+}
+
+/// A WheelBuf that keeps a histogram of its items.
+///
+/// The range `low..high` is divided into `counts.len()` buckets of equal
+/// width; items below or above the range are counted in the first or last
+/// bucket, NaN in the first.
+#[derive(Clone, Debug)]
+pub struct RollingHistogram<C, I, B>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          B: AsMut<[usize]> + AsRef<[usize]>
+{
+    buffer: WheelBuf<C, I>,
+    counts: B,
+    low: f64,
+    high: f64,
+}
+
+impl<C, I, B> RollingHistogram<C, I, B>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Into<f64> + Copy,
+          B: AsMut<[usize]> + AsRef<[usize]>
+{
+    /// Creates a new, empty RollingHistogram.
+    ///
+    /// The contents of `counts` are reset to zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `counts` is empty or `low` is not below `high`.
+    pub fn new(data: C, mut counts: B, low: f64, high: f64) -> RollingHistogram<C, I, B> {
+        assert!(!counts.as_ref().is_empty(), "histogram needs at least one bucket");
+        assert!(low < high, "empty histogram range {}..{}", low, high);
+
+        for count in counts.as_mut() {
+            *count = 0;
+        }
+
+        RollingHistogram {
+            buffer: WheelBuf::from_store(data),
+            counts,
+            low,
+            high,
+        }
+    }
+
+    /// Add item to the window, following the buffer's overflow policy.
+    pub fn push(&mut self, item: I) {
+        let total = self.buffer.total();
+        let evicted = self.buffer.push_evict(item);
+        if self.buffer.total() == total {
+            return;
+        }
+
+        if let Some(old) = evicted {
+            let bucket = self.bucket(old.into());
+            self.counts.as_mut()[bucket] -= 1;
+        }
+        let bucket = self.bucket(item.into());
+        self.counts.as_mut()[bucket] += 1;
+    }
+
+    /// Removes the oldest item from the window.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<I>
+        where I: Default
+    {
+        let item = self.buffer.pop_front()?;
+        let bucket = self.bucket(item.into());
+        self.counts.as_mut()[bucket] -= 1;
+        Some(item)
+    }
+
+    /// Removes all items from the window.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        for count in self.counts.as_mut() {
+            *count = 0;
+        }
+    }
+
+    /// Number of items in each bucket.
+    #[inline]
+    pub fn counts(&self) -> &[usize] {
+        self.counts.as_ref()
+    }
+
+    /// Index of the bucket that counts `x`.
+    #[inline]
+    pub fn bucket(&self, x: f64) -> usize {
+        let buckets = self.counts.as_ref().len();
+        let scaled = (x - self.low) / (self.high - self.low) * buckets as f64;
+
+        // Casting saturates, and maps NaN to zero.
+        cmp::min(scaled as usize, buckets - 1)
+    }
+
+    /// Returns the wrapped buffer.
+    #[inline]
+    pub fn buffer(&self) -> &WheelBuf<C, I> {
+        &self.buffer
+    }
+
+    /// Unwraps the buffer.
+    #[inline]
+    pub fn into_inner(self) -> WheelBuf<C, I> {
+        self.buffer
+    }
+}
+
+/// A local extremum found by `WheelBuf::peaks()`.
+///
+/// Holds the logical index of the item and the item itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Extremum<'a, I>
+    where I: 'a
+{
+    /// A local maximum
+    Peak(usize, &'a I),
+
+    /// A local minimum
+    Trough(usize, &'a I),
+}
+
+/// Iterator over the alternating peaks and troughs of a WheelBuf.
+#[derive(Debug)]
+pub struct WheelBufPeaks<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: 'a,
+          C: 'a
+{
+    iter: WheelBufIter<'a, C, I>,
+    prominence: I,
+
+    /// Logical index of the next item
+    index: usize,
+
+    /// Highest item since the last trough
+    high: Option<(usize, &'a I)>,
+
+    /// Lowest item since the last peak
+    low: Option<(usize, &'a I)>,
+
+    /// `Some(true)` once rising towards a peak, `Some(false)` when falling
+    rising: Option<bool>,
+}
+
+impl<C, I> WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Sub<Output = I> + PartialOrd + Copy
+{
+    /// Creates an iterator over the local maxima and minima, oldest first.
+    ///
+    /// An item counts as a peak once the items after it have fallen at
+    /// least `prominence` below it, and as a trough once they have risen at
+    /// least `prominence` above it. Peaks and troughs alternate; the last
+    /// extremum is not yielded until such a swing confirms it.
+    #[inline]
+    pub fn peaks<'a>(&'a self, prominence: I) -> WheelBufPeaks<'a, C, I> {
+        WheelBufPeaks {
+            iter: self.iter(),
+            prominence,
+            index: 0,
+            high: None,
+            low: None,
+            rising: None,
+        }
+    }
+}
+
+impl<'a, C, I> Iterator for WheelBufPeaks<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Sub<Output = I> + PartialOrd + Copy
+{
+    type Item = Extremum<'a, I>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for x in self.iter.by_ref() {
+            let i = self.index;
+            self.index += 1;
+
+            let (high, low) = match (self.high, self.low) {
+                (Some(high), Some(low)) => (high, low),
+                _ => {
+                    self.high = Some((i, x));
+                    self.low = Some((i, x));
+                    continue;
+                }
+            };
+
+            let high = if x > high.1 { (i, x) } else { high };
+            let low = if x < low.1 { (i, x) } else { low };
+            self.high = Some(high);
+            self.low = Some(low);
+
+            if self.rising != Some(false) && *high.1 - *x >= self.prominence {
+                self.rising = Some(false);
+                self.low = Some((i, x));
+                return Some(Extremum::Peak(high.0, high.1));
+            }
+            if self.rising != Some(true) && *x - *low.1 >= self.prominence {
+                self.rising = Some(true);
+                self.high = Some((i, x));
+                return Some(Extremum::Trough(low.0, low.1));
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, C, I> FusedIterator for WheelBufPeaks<'a, C, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: Sub<Output = I> + PartialOrd + Copy
+{
+}
+
+/// Sorts a few values by insertion.
+fn sort(values: &mut [f64]) {
+    for i in 1..values.len() {
+        let mut j = i;
+        while j > 0 && values[j - 1] > values[j] {
+            values.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+/// Square root, which `core` does not provide.
+#[cfg(not(feature = "std"))]
+fn sqrt(x: f64) -> f64 {
+    if x.is_nan() || x <= 0.0 || x.is_infinite() {
+        return x;
+    }
+
+    // Halving the exponent gives an estimate within a few percent, which
+    // Newton's method refines to full precision in a handful of steps.
+    let mut y = f64::from_bits((x.to_bits() >> 1) + (1023 << 51));
+    for _ in 0..6 {
+        y = 0.5 * (y + x / y);
+    }
+    y
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Overflow;
+
+    #[test]
+    fn rolling_sum() {
+        let mut rolling = RollingSum::new([0i32; 3]);
+        assert_eq!(rolling.mean(), None);
+
+        rolling.push(1);
+        rolling.push(2);
+        assert_eq!(rolling.sum(), 3);
+        assert_eq!(rolling.mean(), Some(1.5));
+
+        rolling.push(3);
+        rolling.push(10);
+        assert_eq!(rolling.sum(), 15);
+        assert_eq!(rolling.pop_front(), Some(2));
+        assert_eq!(rolling.sum(), 13);
+
+        let mut buffer = rolling.into_inner();
+        buffer.push(4);
+        buffer.set_overflow(Overflow::Reject);
+        let mut rolling = RollingSum::from_buffer(buffer);
+        assert_eq!(rolling.sum(), 17);
+        rolling.push(100);
+        assert_eq!(rolling.sum(), 17);
+    }
+
+    #[test]
+    fn rolling_min_max() {
+        let mut rolling = RollingMinMax::new([0i32; 3], [0; 3], [0; 3]);
+        assert_eq!(rolling.min(), None);
+
+        let mut extremes = std::vec::Vec::new();
+        for &x in &[5, 3, 4, 1, 2, 2, 6, 0] {
+            rolling.push(x);
+            extremes.push((*rolling.min().unwrap(), *rolling.max().unwrap()));
+        }
+        assert_eq!(extremes, [(5, 5), (3, 5), (3, 5), (1, 4), (1, 4), (1, 2), (2, 6), (0, 6)]);
+
+        assert_eq!(rolling.pop_front(), Some(2));
+        assert_eq!((rolling.min(), rolling.max()), (Some(&0), Some(&6)));
+        assert_eq!(rolling.pop_front(), Some(6));
+        assert_eq!((rolling.min(), rolling.max()), (Some(&0), Some(&0)));
+    }
+
+    #[test]
+    fn rolling_variance() {
+        let mut rolling = RollingVariance::new([0.0f64; 4]);
+        assert_eq!(rolling.variance(), None);
+
+        for &x in &[100.0, 1.0, 2.0, 3.0, 4.0, 5.0] {
+            rolling.push(x);
+        }
+        assert!((rolling.mean().unwrap() - 3.5).abs() < 1e-12);
+        assert!((rolling.variance().unwrap() - 1.25).abs() < 1e-12);
+        assert!((rolling.sample_variance().unwrap() - 5.0 / 3.0).abs() < 1e-12);
+        assert!((rolling.stddev().unwrap() - 1.25f64.sqrt()).abs() < 1e-12);
+
+        rolling.pop_front();
+        rolling.pop_front();
+        rolling.pop_front();
+        assert_eq!(rolling.variance(), Some(0.0));
+        assert_eq!(rolling.sample_variance(), None);
+        rolling.pop_front();
+        assert_eq!(rolling.mean(), None);
+
+        let rolling = RollingVariance::from_buffer(WheelBuf::new_full([2u8, 4, 4, 4, 5, 5, 7, 9]));
+        assert_eq!(rolling.variance(), Some(4.0));
+        assert_eq!(rolling.stddev(), Some(2.0));
+    }
+
+    #[test]
+    fn moving_averages() {
+        let mut wheel = WheelBuf::new([0u8; 3]);
+        assert_eq!(wheel.ema(0.5), None);
+
+        wheel.extend(1..5);
+        assert_eq!(wheel.ema(0.5), Some(3.25));
+        assert_eq!(wheel.ema(1.0), Some(4.0));
+
+        assert_eq!(wheel.weighted_mean(&[3.0, 1.0]), Some(3.75));
+        assert_eq!(wheel.weighted_mean(&[1.0; 8]), Some(3.0));
+        assert_eq!(wheel.weighted_mean(&[0.0]), None);
+    }
+
+    #[test]
+    fn dot() {
+        let mut wheel = WheelBuf::new([0i32; 4]);
+        assert_eq!(wheel.dot(&[1, 2]), 0);
+
+        wheel.extend(1..7);
+        assert_eq!(wheel.as_slices(), (&[3, 4][..], &[5, 6][..]));
+        assert_eq!(wheel.dot(&[1]), 6);
+        assert_eq!(wheel.dot(&[1, 10, 100]), 6 + 50 + 400);
+        assert_eq!(wheel.dot(&[1, 1, 1, 1, 1]), 18);
+
+        wheel.pop_front();
+        wheel.pop_front();
+        assert_eq!(wheel.dot(&[2, 1]), 17);
+    }
+
+    #[test]
+    fn median_into() {
+        let mut wheel = WheelBuf::new([0i32; 5]);
+        let mut scratch = [0; 5];
+        assert_eq!(wheel.median_into(&mut scratch), None);
+
+        wheel.extend([9, 1, 7, 100, 3, 2].iter().cloned());
+        assert_eq!(wheel.median_into(&mut scratch), Some(&3));
+        wheel.pop_front();
+        assert_eq!(wheel.median_into(&mut scratch), Some(&3));
+        assert_eq!(wheel.len(), 4);
+    }
+
+    #[test]
+    fn p2_quantile() {
+        let mut p95 = P2Quantile::new(0.95);
+        let mut median = P2Quantile::new(0.5);
+        assert_eq!(median.estimate(), None);
+
+        for &x in &[5.0, 1.0, 3.0] {
+            median.push(x);
+        }
+        assert_eq!(median.estimate(), Some(3.0));
+
+        median.reset();
+        // Visits 0..1000 in a scrambled order.
+        for i in 0..1000u32 {
+            let x = f64::from(i * 337 % 1000);
+            p95.push(x);
+            median.push(x);
+        }
+        assert!((median.estimate().unwrap() - 500.0).abs() < 20.0);
+        assert!((p95.estimate().unwrap() - 950.0).abs() < 20.0);
+    }
+
+    #[test]
+    fn p2_quantile_five() {
+        let mut p95 = P2Quantile::new(0.95);
+        let mut p25 = P2Quantile::new(0.25);
+        for &x in &[4.0, 2.0, 5.0, 1.0, 3.0] {
+            p95.push(x);
+            p25.push(x);
+        }
+        assert_eq!(p95.estimate(), Some(5.0));
+        assert_eq!(p25.estimate(), Some(2.0));
+    }
+
+    #[test]
+    fn rolling_quantile() {
+        let mut rolling = RollingQuantile::new([0u16; 100], 0.5);
+        assert_eq!(rolling.quantile(), None);
+
+        for i in 0..1000 {
+            rolling.push(i % 10);
+        }
+        assert!((rolling.quantile().unwrap() - 4.5).abs() <= 1.0);
+
+        for i in 0..100 {
+            rolling.push(1000 + i % 10);
+        }
+        assert!((rolling.quantile().unwrap() - 1004.5).abs() <= 1.0);
+    }
+
+    #[test]
+    fn rolling_histogram() {
+        let mut rolling = RollingHistogram::new([0.0f32; 4], [7; 4], 0.0, 1.0);
+        assert_eq!(rolling.counts(), [0, 0, 0, 0]);
+
+        for &x in &[0.1, 0.6, -3.0, 0.3, 0.99, 1.0] {
+            rolling.push(x);
+        }
+        assert_eq!(rolling.counts(), [1, 1, 0, 2]);
+        assert_eq!(rolling.bucket(f64::NAN), 0);
+
+        rolling.push(0.7);
+        assert_eq!(rolling.counts(), [0, 1, 1, 2]);
+        assert_eq!(rolling.pop_front(), Some(0.3));
+        assert_eq!(rolling.counts(), [0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn peaks() {
+        let mut wheel = WheelBuf::new([0u8; 12]);
+        wheel.extend([9, 9, 0, 5, 4, 5, 1, 8, 7, 9, 3, 2, 4, 3].iter().cloned());
+
+        let extrema: std::vec::Vec<Extremum<u8>> = wheel.peaks(3).collect();
+        assert_eq!(extrema,
+                   [Extremum::Trough(0, &0), Extremum::Peak(1, &5), Extremum::Trough(4, &1),
+                    Extremum::Peak(7, &9)]);
+        assert_eq!(wheel.peaks(10).count(), 0);
+    }
+}
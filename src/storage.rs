@@ -0,0 +1,110 @@
+//! Backing stores for buffers whose soundness depends on them.
+//!
+//! `AsRef` and `AsMut` are safe to implement, so nothing keeps them from
+//! handing out different slices from one call to the next, or slices that
+//! other buffers use as well. Buffers that keep uninitialized or shared
+//! slots instead accept only the store types listed here.
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A backing store that owns or exclusively borrows its slots, and always
+/// yields the same slice of them.
+///
+/// Implemented for arrays, mutable borrows of arrays and slices, and, with
+/// the `alloc` feature, boxed slices and vectors. The trait is sealed, so a
+/// store with a shared borrow cannot be used:
+///
+/// ```compile_fail
+/// use wheelbuf::mpmc::{MpmcWheelBuf, Slot};
+///
+/// let slots = [Slot::new(), Slot::new()];
+/// let _a = MpmcWheelBuf::<_, u8>::new(&slots[..]);
+/// ```
+pub trait Storage<T>: sealed::Sealed {
+    #[doc(hidden)]
+    fn slots(&self) -> &[T];
+
+    #[doc(hidden)]
+    fn slots_mut(&mut self) -> &mut [T];
+}
+
+impl<T, const N: usize> sealed::Sealed for [T; N] {}
+
+impl<T, const N: usize> Storage<T> for [T; N] {
+    #[inline]
+    fn slots(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, const N: usize> sealed::Sealed for &mut [T; N] {}
+
+impl<T, const N: usize> Storage<T> for &mut [T; N] {
+    #[inline]
+    fn slots(&self) -> &[T] {
+        &self[..]
+    }
+
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [T] {
+        &mut self[..]
+    }
+}
+
+impl<T> sealed::Sealed for &mut [T] {}
+
+impl<T> Storage<T> for &mut [T] {
+    #[inline]
+    fn slots(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> sealed::Sealed for Box<[T]> {}
+
+#[cfg(feature = "alloc")]
+impl<T> Storage<T> for Box<[T]> {
+    #[inline]
+    fn slots(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> sealed::Sealed for Vec<T> {}
+
+#[cfg(feature = "alloc")]
+impl<T> Storage<T> for Vec<T> {
+    #[inline]
+    fn slots(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
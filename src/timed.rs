@@ -0,0 +1,135 @@
+//! Buffers of timestamped items.
+//!
+//! A WheelBuf over `(T, I)` pairs stores each item `I` with the tick `T` it
+//! was pushed at. Ticks must not decrease from one push to the next, so the
+//! items are ordered by age and stale ones can be dropped from the front.
+
+use core::ops::Sub;
+
+use super::WheelBuf;
+
+impl<C, T, I> WheelBuf<C, (T, I)>
+    where C: AsMut<[(T, I)]> + AsRef<[(T, I)]>,
+          T: PartialOrd
+{
+    /// Add item to wheel buffer, stamped with `tick`.
+    #[inline]
+    pub fn push_at(&mut self, tick: T, item: I) {
+        self.push((tick, item));
+    }
+
+    /// Removes all items stamped before `deadline`, returning their number.
+    ///
+    /// Stops at the first item that is recent enough, relying on ticks
+    /// being in push order.
+    pub fn expire_older_than(&mut self, deadline: T) -> usize {
+        let stale = self.position(|entry| entry.0 >= deadline).unwrap_or(self.len);
+        let len = self.len;
+        self.truncate_front(len - stale);
+        stale
+    }
+}
+
+/// A WheelBuf of timestamped items that drops items older than a maximum
+/// age on every push.
+#[derive(Clone, Debug)]
+pub struct TtlWheelBuf<C, T, I>
+    where C: AsMut<[(T, I)]> + AsRef<[(T, I)]>
+{
+    buffer: WheelBuf<C, (T, I)>,
+    ttl: T,
+}
+
+impl<C, T, I> TtlWheelBuf<C, T, I>
+    where C: AsMut<[(T, I)]> + AsRef<[(T, I)]>,
+          T: PartialOrd + Sub<Output = T> + Copy
+{
+    /// Creates a new, empty TtlWheelBuf keeping items for `ttl` ticks.
+    #[inline]
+    pub fn new(data: C, ttl: T) -> TtlWheelBuf<C, T, I> {
+        TtlWheelBuf {
+            buffer: WheelBuf::from_store(data),
+            ttl,
+        }
+    }
+
+    /// Drops items older than the TTL at tick `now`, then adds `item`
+    /// stamped with `now`.
+    #[inline]
+    pub fn push(&mut self, now: T, item: I) {
+        self.expire(now);
+        self.buffer.push_at(now, item);
+    }
+
+    /// Drops items older than the TTL at tick `now`, returning their number.
+    #[inline]
+    pub fn expire(&mut self, now: T) -> usize {
+        if now < self.ttl {
+            return 0;
+        }
+        self.buffer.expire_older_than(now - self.ttl)
+    }
+
+    /// Maximum age of items, in ticks.
+    #[inline]
+    pub fn ttl(&self) -> T {
+        self.ttl
+    }
+
+    /// Changes the maximum age, taking effect on the next push.
+    #[inline]
+    pub fn set_ttl(&mut self, ttl: T) {
+        self.ttl = ttl;
+    }
+
+    /// The underlying buffer.
+    #[inline]
+    pub fn buffer(&self) -> &WheelBuf<C, (T, I)> {
+        &self.buffer
+    }
+
+    /// Unwraps the buffer.
+    #[inline]
+    pub fn into_inner(self) -> WheelBuf<C, (T, I)> {
+        self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn expire_older_than() {
+        let mut wheel = WheelBuf::new([(0u32, 'x'); 4]);
+        wheel.push_at(10, 'a');
+        wheel.push_at(20, 'b');
+        wheel.push_at(20, 'c');
+        wheel.push_at(35, 'd');
+        wheel.push_at(40, 'e');
+
+        assert_eq!(wheel.expire_older_than(15), 0);
+        assert_eq!(wheel.expire_older_than(30), 2);
+        let v: Vec<char> = wheel.iter().map(|&(_, c)| c).collect();
+        assert_eq!(v, ['d', 'e']);
+
+        assert_eq!(wheel.expire_older_than(100), 2);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn ttl() {
+        let mut wheel = TtlWheelBuf::new([(0u32, 'x'); 4], 10);
+        wheel.push(3, 'a');
+        wheel.push(8, 'b');
+        wheel.push(14, 'c');
+        let v: Vec<char> = wheel.buffer().iter().map(|&(_, c)| c).collect();
+        assert_eq!(v, ['b', 'c']);
+
+        wheel.push(18, 'd');
+        assert_eq!(wheel.buffer().len(), 3);
+        assert_eq!(wheel.expire(30), 3);
+        assert!(wheel.buffer().is_empty());
+    }
+}
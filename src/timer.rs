@@ -0,0 +1,309 @@
+//! Hierarchical timing wheel.
+//!
+//! Schedules tokens to expire a number of ticks in the future. Timers are
+//! kept in four wheels of 64 slots each, covering ranges of 64, 4096,
+//! 262144 and 16777216 ticks. A timer is put into the finest wheel whose
+//! range reaches its deadline; whenever the finer wheels complete a
+//! revolution, the timers in the next slot of the coarser wheel are
+//! redistributed. Scheduling is O(1), and each timer is moved at most once
+//! per wheel.
+//!
+//! Timers are stored in a pool of `TimerEntry` slots provided by the
+//! caller, linked into per-slot lists by index, so no allocation is needed.
+
+use core::marker::PhantomData;
+use core::mem;
+
+use super::PushError;
+
+/// Bits of the deadline selecting a slot within a wheel
+const SLOT_BITS: u32 = 6;
+
+/// Slots per wheel
+const SLOTS: usize = 1 << SLOT_BITS;
+
+/// Number of wheels
+const LEVELS: usize = 4;
+
+/// End of a list of entries
+const NIL: usize = usize::MAX;
+
+/// A storage slot for a timer in a TimerWheel.
+#[derive(Clone, Copy, Debug)]
+pub struct TimerEntry<T> {
+    token: Option<T>,
+    deadline: u64,
+
+    /// Next entry in the same list
+    next: usize,
+}
+
+impl<T> TimerEntry<T> {
+    /// Creates an unused entry.
+    #[inline]
+    pub const fn new() -> TimerEntry<T> {
+        TimerEntry {
+            token: None,
+            deadline: 0,
+            next: NIL,
+        }
+    }
+}
+
+impl<T> Default for TimerEntry<T> {
+    #[inline]
+    fn default() -> TimerEntry<T> {
+        TimerEntry::new()
+    }
+}
+
+/// A timing wheel holding up to `entries.len()` pending timers.
+pub struct TimerWheel<E, T>
+    where E: AsMut<[TimerEntry<T>]> + AsRef<[TimerEntry<T>]>
+{
+    /// Pool of timer slots
+    entries: E,
+
+    /// Head of the list of unused entries
+    free: usize,
+
+    /// Heads of the timer lists of each slot of each wheel
+    slots: [[usize; SLOTS]; LEVELS],
+
+    /// Current tick
+    now: u64,
+
+    /// Number of pending timers
+    len: usize,
+
+    _pd: PhantomData<T>,
+}
+
+/// Iterator over the tokens expiring while a TimerWheel advances.
+///
+/// Time moves on as the iterator is consumed. If it is dropped early, the
+/// wheel still advances by the full number of ticks, and the remaining
+/// expired tokens are dropped.
+pub struct TimerExpired<'a, E, T>
+    where E: AsMut<[TimerEntry<T>]> + AsRef<[TimerEntry<T>]>,
+          E: 'a,
+          T: 'a
+{
+    wheel: &'a mut TimerWheel<E, T>,
+
+    /// Ticks still to advance
+    remaining: u64,
+
+    /// Expired entries not yet yielded
+    pending: usize,
+}
+
+impl<E, T> TimerWheel<E, T>
+    where E: AsMut<[TimerEntry<T>]> + AsRef<[TimerEntry<T>]>
+{
+    /// Creates a new TimerWheel without pending timers, at tick `0`.
+    ///
+    /// Tokens left in `entries` are dropped.
+    pub fn new(mut entries: E) -> TimerWheel<E, T> {
+        let pool = entries.as_mut();
+        let count = pool.len();
+        for (i, entry) in pool.iter_mut().enumerate() {
+            entry.token = None;
+            entry.next = if i + 1 < count { i + 1 } else { NIL };
+        }
+
+        TimerWheel {
+            entries,
+            free: if count > 0 { 0 } else { NIL },
+            slots: [[NIL; SLOTS]; LEVELS],
+            now: 0,
+            len: 0,
+            _pd: PhantomData,
+        }
+    }
+
+    /// Current tick.
+    #[inline]
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Number of pending timers.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no timers are pending.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Maximum number of pending timers.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.entries.as_ref().len()
+    }
+
+    /// Schedules `token` to expire `delay` ticks from now.
+    ///
+    /// A delay of zero expires on the next tick, like a delay of one. If all
+    /// entries are in use, the token is returned inside the error.
+    pub fn schedule(&mut self, delay: u64, token: T) -> Result<(), PushError<T>> {
+        if self.free == NIL {
+            return Err(PushError(token));
+        }
+
+        let idx = self.free;
+        let entry = &mut self.entries.as_mut()[idx];
+        self.free = entry.next;
+        entry.token = Some(token);
+        entry.deadline = self.now.saturating_add(delay.max(1));
+
+        self.insert(idx);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Advances the wheel by `ticks`, returning an iterator over the tokens
+    /// that expire on the way, in order of their deadlines.
+    #[inline]
+    pub fn advance<'a>(&'a mut self, ticks: u64) -> TimerExpired<'a, E, T> {
+        TimerExpired {
+            wheel: self,
+            remaining: ticks,
+            pending: NIL,
+        }
+    }
+
+    /// Links entry `idx` into the slot matching its deadline.
+    fn insert(&mut self, idx: usize) {
+        let deadline = self.entries.as_ref()[idx].deadline;
+
+        // The finest wheel covering the highest bit in which deadline and
+        // current tick differ. Deadlines beyond the coarsest wheel are
+        // parked in it and redistributed once it comes around.
+        let differing = (self.now ^ deadline) | (SLOTS as u64 - 1);
+        let level = ((63 - differing.leading_zeros()) / SLOT_BITS) as usize;
+        let level = if level < LEVELS { level } else { LEVELS - 1 };
+        let slot = (deadline >> (SLOT_BITS * level as u32)) as usize % SLOTS;
+
+        let head = mem::replace(&mut self.slots[level][slot], idx);
+        self.entries.as_mut()[idx].next = head;
+    }
+
+    /// Moves to the next tick, returning the list of expired entries.
+    fn tick(&mut self) -> usize {
+        self.now += 1;
+        let now = self.now;
+
+        for level in (1..LEVELS).rev() {
+            let shift = SLOT_BITS * level as u32;
+            if now & ((1 << shift) - 1) != 0 {
+                continue;
+            }
+
+            let slot = (now >> shift) as usize % SLOTS;
+            let mut idx = mem::replace(&mut self.slots[level][slot], NIL);
+            while idx != NIL {
+                let next = self.entries.as_ref()[idx].next;
+                self.insert(idx);
+                idx = next;
+            }
+        }
+
+        mem::replace(&mut self.slots[0][now as usize % SLOTS], NIL)
+    }
+}
+
+impl<'a, E, T> Iterator for TimerExpired<'a, E, T>
+    where E: AsMut<[TimerEntry<T>]> + AsRef<[TimerEntry<T>]>
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            if self.pending != NIL {
+                let wheel = &mut *self.wheel;
+                let idx = self.pending;
+                let entry = &mut wheel.entries.as_mut()[idx];
+
+                self.pending = entry.next;
+                entry.next = wheel.free;
+                wheel.free = idx;
+                wheel.len -= 1;
+                return entry.token.take();
+            }
+
+            if self.remaining == 0 {
+                return None;
+            }
+
+            if self.wheel.is_empty() {
+                self.wheel.now += self.remaining;
+                self.remaining = 0;
+                return None;
+            }
+
+            self.remaining -= 1;
+            self.pending = self.wheel.tick();
+        }
+    }
+}
+
+impl<'a, E, T> Drop for TimerExpired<'a, E, T>
+    where E: AsMut<[TimerEntry<T>]> + AsRef<[TimerEntry<T>]>
+{
+    fn drop(&mut self) {
+        for _ in self.by_ref() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn schedule_advance() {
+        let mut wheel = TimerWheel::new([TimerEntry::new(); 2]);
+        assert_eq!(wheel.schedule(3, 'a'), Ok(()));
+        assert_eq!(wheel.schedule(0, 'b'), Ok(()));
+        assert_eq!(wheel.schedule(1, 'c'), Err(PushError('c')));
+
+        let v: Vec<char> = wheel.advance(2).collect();
+        assert_eq!(v, ['b']);
+        assert_eq!(wheel.now(), 2);
+
+        drop(wheel.advance(5));
+        assert_eq!(wheel.now(), 7);
+        assert!(wheel.is_empty());
+
+        assert!(wheel.advance(1000).next().is_none());
+        assert_eq!(wheel.now(), 1007);
+    }
+
+    #[test]
+    fn deadlines() {
+        let delays = [1, 2, 63, 64, 65, 127, 128, 4095, 4096, 4097, 5000, 262143, 262144, 300000];
+        let mut pool = [TimerEntry::new(); 16];
+        let mut wheel = TimerWheel::new(&mut pool[..]);
+
+        // Start off a wheel boundary.
+        wheel.advance(4000).count();
+        for (i, &delay) in delays.iter().enumerate() {
+            wheel.schedule(delay, i).unwrap();
+        }
+
+        let mut fired = 0;
+        while !wheel.is_empty() {
+            let expired: Vec<usize> = wheel.advance(1).collect();
+            for i in expired {
+                assert_eq!(wheel.now(), 4000 + delays[i], "timer {} fired at the wrong tick", i);
+                fired += 1;
+            }
+        }
+        assert_eq!(fired, delays.len());
+    }
+}
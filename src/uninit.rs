@@ -0,0 +1,321 @@
+//! Ring buffer over uninitialized storage.
+
+use core::cmp;
+use core::iter::Chain;
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::ptr;
+use core::slice;
+
+use super::{wrap, Storage};
+
+/// A multi-read Ringbuffer over possibly uninitialized storage.
+///
+/// Works like `WheelBuf`, but the backing store holds `MaybeUninit<I>`
+/// slots. Only slots holding live items are initialized, so `I` needs to be
+/// neither `Clone` nor `Default`. Evicted and removed items are dropped or
+/// handed back, remaining items are dropped along with the buffer.
+///
+/// Which slots are initialized is only tracked by position, so the store
+/// must always present the same slots, see `Storage`.
+pub struct UninitWheelBuf<C, I>
+    where C: Storage<MaybeUninit<I>>
+{
+    /// Backend store
+    data: C,
+
+    /// Insert position
+    pos: usize,
+
+    /// Number of items currently stored
+    len: usize,
+
+    /// Total items written
+    total: usize,
+
+    _pd: PhantomData<I>,
+}
+
+impl<C, I> UninitWheelBuf<C, I>
+    where C: Storage<MaybeUninit<I>>
+{
+    /// Creates a new, empty UninitWheelBuf.
+    ///
+    /// The contents of `data` are ignored and never dropped. The `len()` of
+    /// data determines the size of the buffer.
+    #[inline]
+    pub const fn new(data: C) -> UninitWheelBuf<C, I> {
+        UninitWheelBuf {
+            data,
+            pos: 0,
+            len: 0,
+            total: 0,
+            _pd: PhantomData,
+        }
+    }
+
+    /// Total number of entries seen.
+    #[inline]
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Capacity of wheel buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.data.slots().len()
+    }
+
+    /// Number of items in buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the buffer holds no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the buffer is filled to capacity.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Add item to wheel buffer, dropping the oldest item if full. A buffer
+    /// without capacity drops `item`.
+    #[inline]
+    pub fn push(&mut self, item: I) {
+        self.push_evict(item);
+    }
+
+    /// Add item to wheel buffer, returning the oldest item if it had to be
+    /// evicted.
+    ///
+    /// A buffer without capacity hands back `item` itself.
+    #[inline]
+    pub fn push_evict(&mut self, item: I) -> Option<I> {
+        if self.capacity() == 0 {
+            return Some(item);
+        }
+
+        let full = self.is_full();
+        let slot = &mut self.data.slots_mut()[self.pos];
+
+        // A full buffer has every slot initialized, including the one at the
+        // insert position.
+        let old = if full {
+            Some(unsafe { slot.assume_init_read() })
+        } else {
+            None
+        };
+        slot.write(item);
+
+        let next = self.pos + 1;
+        self.total += 1;
+        self.pos = if next == self.capacity() { 0 } else { next };
+        self.len += !full as usize;
+        old
+    }
+
+    /// Removes the oldest item from the buffer.
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<I> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let idx = self.read_start();
+        self.len -= 1;
+        Some(unsafe { self.data.slots()[idx].assume_init_read() })
+    }
+
+    /// Removes the most recently pushed item from the buffer.
+    ///
+    /// A retracted item no longer counts towards `total()`.
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<I> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.pos = wrap(self.pos + self.capacity() - 1, self.capacity());
+        self.len -= 1;
+        self.total -= 1;
+        Some(unsafe { self.data.slots()[self.pos].assume_init_read() })
+    }
+
+    /// Removes and drops all items.
+    pub fn clear(&mut self) {
+        let (first, second) = self.as_mut_slices();
+        let (first, second) = (first as *mut [I], second as *mut [I]);
+
+        // Reset first, so a panicking destructor leaks instead of double
+        // dropping.
+        self.pos = 0;
+        self.len = 0;
+        unsafe {
+            ptr::drop_in_place(first);
+            ptr::drop_in_place(second);
+        }
+    }
+
+    /// Returns a reference to the item at logical position `index`.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&I> {
+        if index >= self.len {
+            return None;
+        }
+
+        let idx = wrap(self.read_start() + index, self.capacity());
+        Some(unsafe { self.data.slots()[idx].assume_init_ref() })
+    }
+
+    /// Returns a mutable reference to the item at logical position `index`.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut I> {
+        if index >= self.len {
+            return None;
+        }
+
+        let idx = wrap(self.read_start() + index, self.capacity());
+        Some(unsafe { self.data.slots_mut()[idx].assume_init_mut() })
+    }
+
+    /// Returns the contents of the buffer as two slices, oldest first.
+    #[inline]
+    pub fn as_slices(&self) -> (&[I], &[I]) {
+        if self.is_empty() {
+            return (&[], &[]);
+        }
+
+        let start = self.read_start();
+        let (wrapped, tail) = self.data.slots().split_at(start);
+        let first_len = cmp::min(self.len, tail.len());
+
+        unsafe {
+            (assume_init_slice(&tail[..first_len]), assume_init_slice(&wrapped[..self.len - first_len]))
+        }
+    }
+
+    /// Returns the contents of the buffer as two mutable slices, oldest
+    /// first.
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> (&mut [I], &mut [I]) {
+        if self.is_empty() {
+            return (&mut [], &mut []);
+        }
+
+        let start = self.read_start();
+        let len = self.len;
+        let (wrapped, tail) = self.data.slots_mut().split_at_mut(start);
+        let first_len = cmp::min(len, tail.len());
+
+        unsafe {
+            (assume_init_slice_mut(&mut tail[..first_len]),
+             assume_init_slice_mut(&mut wrapped[..len - first_len]))
+        }
+    }
+
+    /// Creates an iterator over buffer.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> Chain<slice::Iter<'a, I>, slice::Iter<'a, I>> {
+        let (first, second) = self.as_slices();
+        first.iter().chain(second.iter())
+    }
+
+    #[inline]
+    fn read_start(&self) -> usize {
+        wrap(self.pos + self.capacity() - self.len, self.capacity())
+    }
+}
+
+impl<C, I> Drop for UninitWheelBuf<C, I>
+    where C: Storage<MaybeUninit<I>>
+{
+    fn drop(&mut self) {
+        if mem::needs_drop::<I>() {
+            self.clear();
+        }
+    }
+}
+
+/// Reinterprets a slice of initialized slots.
+///
+/// The caller must guarantee every slot is initialized.
+#[inline]
+unsafe fn assume_init_slice<I>(slots: &[MaybeUninit<I>]) -> &[I] {
+    &*(slots as *const [MaybeUninit<I>] as *const [I])
+}
+
+/// Mutable version of `assume_init_slice`.
+#[inline]
+unsafe fn assume_init_slice_mut<I>(slots: &mut [MaybeUninit<I>]) -> &mut [I] {
+    &mut *(slots as *mut [MaybeUninit<I>] as *mut [I])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn basics() {
+        let mut buf: [MaybeUninit<u32>; 3] = [MaybeUninit::uninit(); 3];
+        let mut wheel = UninitWheelBuf::new(&mut buf);
+
+        assert!(wheel.pop_front().is_none());
+        for i in 1..5 {
+            wheel.push(i);
+        }
+        assert_eq!(wheel.len(), 3);
+        assert_eq!(wheel.get(0), Some(&2));
+        assert_eq!(wheel.as_slices(), (&[2, 3][..], &[4][..]));
+
+        *wheel.get_mut(2).unwrap() = 40;
+        assert_eq!(wheel.pop_back(), Some(40));
+        assert_eq!(wheel.pop_front(), Some(2));
+
+        let v: Vec<u32> = wheel.iter().cloned().collect();
+        assert_eq!(v, [3]);
+    }
+
+    #[test]
+    fn drops() {
+        let item = Rc::new(());
+        {
+            let mut buf: [MaybeUninit<Rc<()>>; 2] = [MaybeUninit::uninit(), MaybeUninit::uninit()];
+            let mut wheel = UninitWheelBuf::new(&mut buf);
+
+            wheel.push(item.clone());
+            wheel.push(item.clone());
+            assert!(wheel.push_evict(item.clone()).is_some());
+            assert_eq!(Rc::strong_count(&item), 3);
+
+            wheel.push(item.clone());
+            assert_eq!(Rc::strong_count(&item), 3);
+        }
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn owned_store() {
+        let mut wheel = UninitWheelBuf::new([MaybeUninit::<u32>::uninit(); 2]);
+        wheel.push(1);
+        wheel.push(2);
+        wheel.push(3);
+        assert_eq!(wheel.as_slices(), (&[2][..], &[3][..]));
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut wheel = UninitWheelBuf::<_, u32>::new([]);
+        wheel.push(1);
+        assert_eq!(wheel.push_evict(2), Some(2));
+        assert!(wheel.is_empty());
+        assert_eq!(wheel.total(), 0);
+    }
+}
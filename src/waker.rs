@@ -0,0 +1,82 @@
+//! Waker storage shared between a task and a waking context.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::Waker;
+
+/// No registration or wakeup in progress
+const WAITING: usize = 0;
+
+/// A task is storing its waker
+const REGISTERING: usize = 0b01;
+
+/// A wakeup is taking the waker out
+const WAKING: usize = 0b10;
+
+/// A slot for a single waker that can be registered and woken from
+/// different threads or interrupt handlers.
+///
+/// Follows the state machine of `futures::task::AtomicWaker`: a wakeup that
+/// races with a registration is handed over to the registering task, which
+/// then wakes itself.
+pub(crate) struct AtomicWaker {
+    state: AtomicUsize,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+unsafe impl Send for AtomicWaker {}
+unsafe impl Sync for AtomicWaker {}
+
+impl AtomicWaker {
+    /// Creates an empty slot.
+    #[inline]
+    pub(crate) const fn new() -> AtomicWaker {
+        AtomicWaker {
+            state: AtomicUsize::new(WAITING),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// Stores `waker` to be woken by the next call to `wake()`.
+    pub(crate) fn register(&self, waker: &Waker) {
+        match self.state.compare_exchange(WAITING, REGISTERING, Ordering::Acquire, Ordering::Acquire) {
+            Ok(_) => {
+                // Holding the `REGISTERING` bit grants exclusive access to the
+                // stored waker.
+                unsafe {
+                    let slot = &mut *self.waker.get();
+                    if !slot.as_ref().is_some_and(|old| old.will_wake(waker)) {
+                        *slot = Some(waker.clone());
+                    }
+                }
+
+                if self.state
+                       .compare_exchange(REGISTERING, WAITING, Ordering::AcqRel, Ordering::Acquire)
+                       .is_err() {
+                    // A wakeup arrived in the meantime and left the waker to
+                    // us.
+                    let waker = unsafe { (*self.waker.get()).take() };
+                    self.state.swap(WAITING, Ordering::AcqRel);
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+            Err(WAKING) => waker.wake_by_ref(),
+            Err(_) => {
+                // Registering concurrently with itself; the other call wins.
+            }
+        }
+    }
+
+    /// Wakes the registered task, if any.
+    pub(crate) fn wake(&self) {
+        if self.state.fetch_or(WAKING, Ordering::AcqRel) == WAITING {
+            let waker = unsafe { (*self.waker.get()).take() };
+            self.state.fetch_and(!WAKING, Ordering::Release);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
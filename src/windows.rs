@@ -0,0 +1,358 @@
+//! Iterators over groups of consecutive items.
+
+use core::cmp;
+use core::iter::{FusedIterator, Skip};
+
+use super::WheelBuf;
+
+/// Iterator over overlapping windows of a WheelBuf.
+///
+/// Each window is returned as a pair of slices, which concatenated hold the
+/// window's items in logical order. The second slice is empty unless the
+/// window straddles the end of the backing store.
+#[derive(Clone, Debug)]
+pub struct WheelBufWindows<'a, I>
+    where I: 'a
+{
+    first: &'a [I],
+    second: &'a [I],
+    size: usize,
+    cur: usize,
+}
+
+/// Iterator over non-overlapping chunks of a WheelBuf.
+///
+/// Chunks are returned as slice pairs, like windows. The last chunk is
+/// shorter if the number of items is not a multiple of the chunk size.
+#[derive(Clone, Debug)]
+pub struct WheelBufChunks<'a, I>
+    where I: 'a
+{
+    first: &'a [I],
+    second: &'a [I],
+    size: usize,
+    cur: usize,
+}
+
+/// Iterator over the complete lines of a text WheelBuf.
+///
+/// Lines are returned as slice pairs, like windows, without their
+/// terminating newline.
+#[derive(Clone, Debug)]
+pub struct WheelBufLines<'a, I>
+    where I: 'a
+{
+    first: &'a [I],
+    second: &'a [I],
+    newline: I,
+    cur: usize,
+}
+
+impl<C> WheelBuf<C, char>
+    where C: AsMut<[char]> + AsRef<[char]>
+{
+    /// Creates an iterator over all `'\n'`-terminated lines, oldest first.
+    ///
+    /// Characters after the last newline are skipped, as the line they
+    /// start has not been completed yet. So is the oldest line if pushing
+    /// overwrote its start. Only `write!()` checks whether the overwritten
+    /// character was a newline; other pushes assume it was not.
+    #[inline]
+    pub fn iter_lines<'a>(&'a self) -> WheelBufLines<'a, char> {
+        lines(self, '\n')
+    }
+
+    /// Creates an iterator over the last `n` complete lines, oldest first.
+    #[inline]
+    pub fn tail_lines<'a>(&'a self, n: usize) -> Skip<WheelBufLines<'a, char>> {
+        last(self.iter_lines(), n)
+    }
+}
+
+impl<C> WheelBuf<C, u8>
+    where C: AsMut<[u8]> + AsRef<[u8]>
+{
+    /// Creates an iterator over all `b'\n'`-terminated lines, oldest first.
+    ///
+    /// Bytes after the last newline are skipped, as the line they start has
+    /// not been completed yet. So is the oldest line if pushing overwrote
+    /// its start. Only `write!()` and `io::Write` check whether the
+    /// overwritten byte was a newline; other pushes assume it was not.
+    #[inline]
+    pub fn iter_lines<'a>(&'a self) -> WheelBufLines<'a, u8> {
+        lines(self, b'\n')
+    }
+
+    /// Creates an iterator over the last `n` complete lines, oldest first.
+    #[inline]
+    pub fn tail_lines<'a>(&'a self, n: usize) -> Skip<WheelBufLines<'a, u8>> {
+        last(self.iter_lines(), n)
+    }
+}
+
+impl<C, I> WheelBuf<C, I>
+    where C: AsMut<[I]> + AsRef<[I]>
+{
+    /// Creates an iterator over all overlapping windows of `size` items,
+    /// oldest first.
+    ///
+    /// Yields nothing if the buffer holds fewer than `size` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    #[inline]
+    pub fn windows<'a>(&'a self, size: usize) -> WheelBufWindows<'a, I> {
+        assert!(size != 0, "window size must be non-zero");
+
+        let (first, second) = self.as_slices();
+        WheelBufWindows {
+            first,
+            second,
+            size,
+            cur: 0,
+        }
+    }
+
+    /// Creates an iterator over consecutive chunks of `size` items, oldest
+    /// first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    #[inline]
+    pub fn chunks<'a>(&'a self, size: usize) -> WheelBufChunks<'a, I> {
+        assert!(size != 0, "chunk size must be non-zero");
+
+        let (first, second) = self.as_slices();
+        WheelBufChunks {
+            first,
+            second,
+            size,
+            cur: 0,
+        }
+    }
+}
+
+/// Creates an iterator over the lines of `buffer` ending in `newline`.
+#[inline]
+fn lines<'a, C, I>(buffer: &'a WheelBuf<C, I>, newline: I) -> WheelBufLines<'a, I>
+    where C: AsMut<[I]> + AsRef<[I]>,
+          I: PartialEq
+{
+    let (first, second) = buffer.as_slices();
+    let mut lines = WheelBufLines {
+        first,
+        second,
+        newline,
+        cur: 0,
+    };
+
+    if buffer.line_cut && buffer.overwritten == buffer.seq_start() {
+        // The oldest line was cut off at the front by an overwrite.
+        lines.next();
+    }
+    lines
+}
+
+/// Skips all but the last `n` lines of `lines`.
+#[inline]
+fn last<I>(lines: WheelBufLines<I>, n: usize) -> Skip<WheelBufLines<I>>
+    where I: Clone + PartialEq
+{
+    let count = lines.clone().count();
+    lines.skip(count.saturating_sub(n))
+}
+
+/// Items `start..end` of the concatenation of `first` and `second`.
+#[inline]
+fn range_pair<'a, I>(first: &'a [I], second: &'a [I], start: usize, end: usize) -> (&'a [I], &'a [I]) {
+    let split = first.len();
+
+    if end <= split {
+        (&first[start..end], &[])
+    } else if start >= split {
+        (&second[start - split..end - split], &[])
+    } else {
+        (&first[start..], &second[..end - split])
+    }
+}
+
+impl<'a, I> Iterator for WheelBufWindows<'a, I>
+    where I: 'a
+{
+    type Item = (&'a [I], &'a [I]);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur + self.size > self.first.len() + self.second.len() {
+            return None;
+        }
+
+        let start = self.cur;
+        self.cur += 1;
+        Some(range_pair(self.first, self.second, start, start + self.size))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.first.len() + self.second.len() + 1).saturating_sub(self.cur + self.size);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, I> ExactSizeIterator for WheelBufWindows<'a, I>
+    where I: 'a
+{
+}
+
+impl<'a, I> FusedIterator for WheelBufWindows<'a, I>
+    where I: 'a
+{
+}
+
+impl<'a, I> Iterator for WheelBufChunks<'a, I>
+    where I: 'a
+{
+    type Item = (&'a [I], &'a [I]);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let len = self.first.len() + self.second.len();
+        if self.cur >= len {
+            return None;
+        }
+
+        let start = self.cur;
+        self.cur = cmp::min(start + self.size, len);
+        Some(range_pair(self.first, self.second, start, self.cur))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.first.len() + self.second.len();
+        let remaining = (len - self.cur).div_ceil(self.size);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, I> ExactSizeIterator for WheelBufChunks<'a, I>
+    where I: 'a
+{
+}
+
+impl<'a, I> FusedIterator for WheelBufChunks<'a, I>
+    where I: 'a
+{
+}
+
+impl<'a, I> Iterator for WheelBufLines<'a, I>
+    where I: PartialEq + 'a
+{
+    type Item = (&'a [I], &'a [I]);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let split = self.first.len();
+        let newline = &self.newline;
+        let found = if self.cur < split {
+            self.first[self.cur..]
+                .iter()
+                .position(|c| c == newline)
+                .map(|p| self.cur + p)
+                .or_else(|| self.second.iter().position(|c| c == newline).map(|p| split + p))
+        } else {
+            self.second[self.cur - split..].iter().position(|c| c == newline).map(|p| self.cur + p)
+        };
+
+        match found {
+            Some(end) => {
+                let start = self.cur;
+                self.cur = end + 1;
+                Some(range_pair(self.first, self.second, start, end))
+            }
+            None => {
+                self.cur = split + self.second.len();
+                None
+            }
+        }
+    }
+}
+
+impl<'a, I> FusedIterator for WheelBufLines<'a, I>
+    where I: PartialEq + 'a
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+    use std::string::String;
+    use std::vec::Vec;
+    use super::*;
+
+    #[test]
+    fn windows() {
+        let mut wheel = WheelBuf::new([0; 4]);
+        wheel.extend(1..7);
+
+        let w: Vec<(&[i32], &[i32])> = wheel.windows(3).collect();
+        assert_eq!(w, [(&[3, 4][..], &[5][..]), (&[4][..], &[5, 6][..])]);
+
+        let sums: Vec<i32> = wheel.windows(2).map(|(a, b)| a.iter().chain(b).sum()).collect();
+        assert_eq!(sums, [7, 9, 11]);
+
+        assert_eq!(wheel.windows(4).len(), 1);
+        assert_eq!(wheel.windows(5).count(), 0);
+    }
+
+    #[test]
+    fn chunks() {
+        let mut wheel = WheelBuf::new([0; 5]);
+        wheel.extend(1..8);
+
+        let c: Vec<(&[i32], &[i32])> = wheel.chunks(2).collect();
+        assert_eq!(c, [(&[3, 4][..], &[][..]), (&[5][..], &[6][..]), (&[7][..], &[][..])]);
+        assert_eq!(wheel.chunks(2).len(), 3);
+        assert_eq!(wheel.chunks(5).len(), 1);
+    }
+
+    #[test]
+    fn iter_lines() {
+        let mut wheel = WheelBuf::new(['x'; 12]);
+        write!(wheel, "one\ntwo\n\nthree\nfo").unwrap();
+
+        let lines: Vec<String> = wheel.iter_lines().map(|(a, b)| a.iter().chain(b).collect()).collect();
+        assert_eq!(lines, ["", "three"]);
+
+        let lines: Vec<String> = wheel.tail_lines(1).map(|(a, b)| a.iter().chain(b).collect()).collect();
+        assert_eq!(lines, ["three"]);
+        assert_eq!(wheel.tail_lines(5).count(), 2);
+
+        let mut bytes = WheelBuf::new([0u8; 8]);
+        write!(bytes, "ab\ncd\n").unwrap();
+        let lines: Vec<(&[u8], &[u8])> = bytes.iter_lines().collect();
+        assert_eq!(lines, [(&b"ab"[..], &b""[..]), (&b"cd"[..], &b""[..])]);
+    }
+
+    #[test]
+    fn iter_lines_whole() {
+        let mut wheel = WheelBuf::new(['x'; 12]);
+        write!(wheel, "one\ntwo\n").unwrap();
+        for _ in 0..4 {
+            wheel.pop_front();
+        }
+
+        let lines: Vec<String> = wheel.iter_lines().map(|(a, b)| a.iter().chain(b).collect()).collect();
+        assert_eq!(lines, ["two"]);
+
+        // Overwriting up to a newline does not cut a line either.
+        let mut bytes = WheelBuf::new([0u8; 3]);
+        write!(bytes, "ab\ncd\n").unwrap();
+        let lines: Vec<(&[u8], &[u8])> = bytes.iter_lines().collect();
+        assert_eq!(lines, [(&b"cd"[..], &b""[..])]);
+
+        bytes.push(b'e');
+        assert_eq!(bytes.iter_lines().count(), 0);
+    }
+}